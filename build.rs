@@ -13,9 +13,23 @@ fn main() -> anyhow::Result<()> {
 
 #[cfg(feature = "riscv-ulp-hal")]
 fn main() {
-    println!("cargo:rustc-cfg=esp32s2");
+    // The ULP HAL is built without `esp-idf-sys`, so the target chip is taken from the same
+    // `MCU` environment variable that `esp-idf-sys` uses to select it
+    println!("cargo:rerun-if-env-changed=MCU");
+
+    let mcu = std::env::var("MCU").unwrap_or_else(|_| "esp32s2".into());
+    if mcu != "esp32s2" && mcu != "esp32s3" {
+        panic!(
+            "Feature `riscv-ulp-hal` is only supported on esp32s2 and esp32s3, got MCU={}",
+            mcu
+        );
+    }
+
+    println!("cargo:rustc-cfg={}", mcu);
 
     let riscv_ulp_dir = std::env::current_dir().unwrap().join("riscv-ulp");
+    let riscv_ulp_mcu_dir = riscv_ulp_dir.join(&mcu);
+    println!("cargo:rustc-link-search={}", riscv_ulp_mcu_dir.display());
     println!("cargo:rustc-link-search={}", riscv_ulp_dir.display());
 
     println!(
@@ -28,6 +42,6 @@ fn main() {
     );
     println!(
         "cargo:rerun-if-changed={}",
-        riscv_ulp_dir.join("riscv_ulp_link_default.x").display()
+        riscv_ulp_mcu_dir.join("riscv_ulp_link_default.x").display()
     );
 }
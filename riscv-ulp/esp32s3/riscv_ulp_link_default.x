@@ -0,0 +1,4 @@
+/* The ESP32-S3 ULP RISC-V shares the 8K RTC slow memory with the main core, same as on the ESP32-S2.
+   Adjust to match CONFIG_ULP_COPROC_RESERVE_MEM of the main core project. */
+PROVIDE(_ram_size = 4K);
+INCLUDE riscv_ulp_link_base.x;
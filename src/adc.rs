@@ -6,10 +6,8 @@ use esp_idf_sys::*;
 #[cfg(feature = "riscv-ulp-hal")]
 use crate::riscv_ulp_hal::sys::*;
 
-#[cfg(not(feature = "riscv-ulp-hal"))]
 use crate::gpio::ADCPin;
 
-#[cfg(not(feature = "riscv-ulp-hal"))]
 use crate::peripheral::{Peripheral, PeripheralRef};
 
 #[cfg(not(feature = "riscv-ulp-hal"))]
@@ -404,6 +402,97 @@ fn to_nb_err(err: EspError) -> nb::Error<EspError> {
     }
 }
 
+/// ADC channel driver for the RiscV ULP coprocessor
+///
+/// Attenuation and bit width of the channel are configured by the main core when it hands
+/// the ADC over to the ULP (`ulp_adc_init` / `adc1_ulp_enable`), so `ATTEN` only documents
+/// the configuration the ULP program expects.
+#[cfg(feature = "riscv-ulp-hal")]
+pub struct AdcChannelDriver<'d, T: ADCPin, ATTEN> {
+    pin: PeripheralRef<'d, T>,
+    _atten: PhantomData<ATTEN>,
+}
+
+#[cfg(feature = "riscv-ulp-hal")]
+impl<'d, T: ADCPin, ATTEN> AdcChannelDriver<'d, T, ATTEN>
+where
+    ATTEN: Attenuation<T::Adc>,
+{
+    #[inline]
+    pub fn new(
+        pin: impl Peripheral<P = T> + 'd,
+    ) -> Result<AdcChannelDriver<'d, T, ATTEN>, EspError> {
+        crate::into_ref!(pin);
+
+        Ok(Self {
+            pin,
+            _atten: PhantomData,
+        })
+    }
+
+    fn pin(&mut self) -> &mut PeripheralRef<'d, T> {
+        &mut self.pin
+    }
+}
+
+#[cfg(feature = "riscv-ulp-hal")]
+impl<'d, T: ADCPin, ATTEN> embedded_hal_0_2::adc::Channel<ATTEN>
+    for AdcChannelDriver<'d, T, ATTEN>
+{
+    type ID = u8;
+
+    fn channel() -> Self::ID {
+        T::CHANNEL as _
+    }
+}
+
+/// ADC driver for the RiscV ULP coprocessor
+///
+/// Conversions are triggered through the RTC controller of the SAR ADC, which
+/// the main core has to enable for ULP use before starting the ULP program.
+#[cfg(feature = "riscv-ulp-hal")]
+pub struct AdcDriver<'d, ADC: Adc> {
+    _adc: PeripheralRef<'d, ADC>,
+}
+
+#[cfg(feature = "riscv-ulp-hal")]
+impl<'d, ADC: Adc> AdcDriver<'d, ADC> {
+    pub fn new(adc: impl Peripheral<P = ADC> + 'd) -> Result<Self, EspError> {
+        crate::into_ref!(adc);
+
+        Ok(Self { _adc: adc })
+    }
+
+    pub fn read<'c, T, ATTEN>(
+        &mut self,
+        pin: &mut AdcChannelDriver<'c, T, ATTEN>,
+    ) -> Result<u16, EspError>
+    where
+        T: ADCPin,
+        ATTEN: Attenuation<T::Adc>,
+    {
+        Ok(self.read_internal(pin.pin().adc_channel()))
+    }
+
+    fn read_internal(&mut self, channel: adc_channel_t) -> u16 {
+        unsafe { ulp_riscv_adc_read_channel(ADC::unit(), channel) as u16 }
+    }
+}
+
+#[cfg(feature = "riscv-ulp-hal")]
+impl<'d, ADC, ATTEN, PIN> embedded_hal_0_2::adc::OneShot<ATTEN, u16, PIN> for AdcDriver<'d, ADC>
+where
+    ADC: Adc,
+    ATTEN: Attenuation<ADC>,
+    PIN: embedded_hal_0_2::adc::Channel<ATTEN, ID = u8>,
+{
+    type Error = EspError;
+
+    fn read(&mut self, _pin: &mut PIN) -> nb::Result<u16, Self::Error> {
+        Ok(self.read_internal(PIN::channel() as adc_channel_t))
+    }
+}
+
 macro_rules! impl_adc {
     ($adc:ident: $unit:expr) => {
         crate::impl_peripheral!($adc);
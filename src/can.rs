@@ -51,6 +51,8 @@ pub type CanConfig = config::Config;
 pub mod config {
     use esp_idf_sys::*;
 
+    use crate::interrupt::{InterruptConfig, InterruptLevel};
+
     /// CAN timing
     #[derive(Debug, Copy, Clone, Eq, PartialEq)]
     pub enum Timing {
@@ -205,10 +207,11 @@ pub mod config {
         }
     }
 
-    #[derive(Debug, Copy, Clone, Default)]
+    #[derive(Debug, Copy, Clone)]
     pub struct Config {
         pub timing: Timing,
         pub filter: Filter,
+        pub interrupt: InterruptConfig,
     }
 
     impl Config {
@@ -227,6 +230,22 @@ pub mod config {
             self.filter = filter;
             self
         }
+
+        #[must_use]
+        pub fn interrupt(mut self, interrupt: InterruptConfig) -> Self {
+            self.interrupt = interrupt;
+            self
+        }
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                timing: Default::default(),
+                filter: Default::default(),
+                interrupt: InterruptConfig::new().level(Some(InterruptLevel::Level1)),
+            }
+        }
     }
 }
 
@@ -254,7 +273,7 @@ impl<'d> CanDriver<'d> {
             rx_queue_len: 5,
             alerts_enabled: TWAI_ALERT_NONE,
             clkout_divider: 0,
-            intr_flags: ESP_INTR_FLAG_LEVEL1 as i32,
+            intr_flags: config.interrupt.into(),
         };
 
         let timing_config = config.timing.into();
@@ -0,0 +1,357 @@
+//! High resolution software timers, based on the ESP-IDF `esp_timer` service.
+//!
+//! Unlike the hardware group timers in `timer`, the `esp_timer` service multiplexes
+//! an unlimited number of software timers on top of a single hardware timer,
+//! with a resolution of one microsecond.
+//!
+//! Timer callbacks are either dispatched from the `esp_timer` task (`EspTaskTimerService`),
+//! or - if `CONFIG_ESP_TIMER_SUPPORTS_ISR_DISPATCH_METHOD` is enabled - directly from
+//! the timer ISR (`EspIsrTimerService`).
+
+use core::marker::PhantomData;
+use core::time::Duration;
+
+use esp_idf_sys::*;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+
+/// Returns the time elapsed since boot, with a microsecond resolution
+pub fn now() -> Duration {
+    Duration::from_micros(unsafe { esp_timer_get_time() } as _)
+}
+
+pub trait EspTimerServiceType {
+    fn dispatch_method() -> esp_timer_dispatch_t;
+}
+
+/// Timer callbacks are executed in the context of the `esp_timer` task
+#[derive(Clone, Debug)]
+pub struct Task;
+
+impl EspTimerServiceType for Task {
+    fn dispatch_method() -> esp_timer_dispatch_t {
+        esp_timer_dispatch_t_ESP_TIMER_TASK
+    }
+}
+
+/// Timer callbacks are executed directly in the timer ISR context
+#[cfg(esp_idf_esp_timer_supports_isr_dispatch_method)]
+#[derive(Clone, Debug)]
+pub struct Isr;
+
+#[cfg(esp_idf_esp_timer_supports_isr_dispatch_method)]
+impl EspTimerServiceType for Isr {
+    fn dispatch_method() -> esp_timer_dispatch_t {
+        esp_timer_dispatch_t_ESP_TIMER_ISR
+    }
+}
+
+pub type EspTaskTimerService = EspTimerService<Task>;
+#[cfg(esp_idf_esp_timer_supports_isr_dispatch_method)]
+pub type EspIsrTimerService = EspTimerService<Isr>;
+
+#[derive(Clone, Debug)]
+pub struct EspTimerService<T>(PhantomData<T>)
+where
+    T: EspTimerServiceType;
+
+impl EspTimerService<Task> {
+    pub fn new() -> Result<Self, EspError> {
+        Ok(Self(PhantomData))
+    }
+}
+
+#[cfg(esp_idf_esp_timer_supports_isr_dispatch_method)]
+impl EspTimerService<Isr> {
+    /// # Safety
+    ///
+    /// Care should be taken not to call STD, libc or FreeRTOS APIs (except for a few allowed ones)
+    /// in the callbacks of the timers created by this service, as these are executed in an ISR context.
+    pub unsafe fn new() -> Result<Self, EspError> {
+        Ok(Self(PhantomData))
+    }
+}
+
+impl<T> EspTimerService<T>
+where
+    T: EspTimerServiceType,
+{
+    pub fn now(&self) -> Duration {
+        now()
+    }
+
+    /// Creates a new timer which calls `callback` when it fires.
+    ///
+    /// The timer is initially not scheduled; use `EspTimer::after` for a one-shot
+    /// or `EspTimer::every` for a periodic schedule.
+    #[cfg(feature = "alloc")]
+    pub fn timer<F>(&self, callback: F) -> Result<EspTimer, EspError>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let mut callback: Box<Box<dyn FnMut() + Send + 'static>> = Box::new(Box::new(callback));
+
+        let mut handle: esp_timer_handle_t = core::ptr::null_mut();
+
+        esp!(unsafe {
+            esp_timer_create(
+                &esp_timer_create_args_t {
+                    callback: Some(EspTimer::handle),
+                    name: b"rust\0" as *const _ as *const _,
+                    arg: callback.as_mut() as *mut _ as *mut _,
+                    dispatch_method: T::dispatch_method(),
+                    skip_unhandled_events: false,
+                },
+                &mut handle as *mut _,
+            )
+        })?;
+
+        Ok(EspTimer {
+            handle,
+            _callback: callback,
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub struct EspTimer {
+    handle: esp_timer_handle_t,
+    _callback: Box<Box<dyn FnMut() + Send + 'static>>,
+}
+
+#[cfg(feature = "alloc")]
+impl EspTimer {
+    /// Cancels the timer if it is scheduled.
+    ///
+    /// Returns `true` if the timer was scheduled and got cancelled.
+    pub fn cancel(&self) -> Result<bool, EspError> {
+        let res = unsafe { esp_timer_stop(self.handle) };
+
+        if res == ESP_ERR_INVALID_STATE {
+            // The timer was not scheduled
+            Ok(false)
+        } else {
+            esp!(res)?;
+
+            Ok(true)
+        }
+    }
+
+    /// Schedules the timer to fire once, after `duration` has elapsed.
+    ///
+    /// Cancels any previous schedule.
+    pub fn after(&self, duration: Duration) -> Result<(), EspError> {
+        self.cancel()?;
+
+        esp!(unsafe { esp_timer_start_once(self.handle, duration.as_micros() as _) })?;
+
+        Ok(())
+    }
+
+    /// Schedules the timer to fire periodically, every `duration`.
+    ///
+    /// Cancels any previous schedule.
+    pub fn every(&self, duration: Duration) -> Result<(), EspError> {
+        self.cancel()?;
+
+        esp!(unsafe { esp_timer_start_periodic(self.handle, duration.as_micros() as _) })?;
+
+        Ok(())
+    }
+
+    extern "C" fn handle(arg: *mut c_types::c_void) {
+        let callback = unsafe { (arg as *mut Box<dyn FnMut() + Send + 'static>).as_mut() }.unwrap();
+
+        if crate::interrupt::active() {
+            #[cfg(esp_idf_esp_timer_supports_isr_dispatch_method)]
+            {
+                let signaled = crate::interrupt::with_isr_yield_signal(callback);
+
+                if signaled {
+                    unsafe {
+                        esp_timer_isr_dispatch_need_yield();
+                    }
+                }
+            }
+        } else {
+            callback();
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for EspTimer {
+    fn drop(&mut self) {
+        self.cancel().unwrap();
+
+        esp!(unsafe { esp_timer_delete(self.handle) }).unwrap();
+    }
+}
+
+#[cfg(feature = "alloc")]
+unsafe impl Send for EspTimer {}
+
+#[cfg(feature = "embassy-time-driver")]
+pub mod embassy_time {
+    pub mod driver {
+        use core::cell::UnsafeCell;
+        use core::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+        use esp_idf_sys::*;
+
+        use embassy_time::driver::{AlarmHandle, Driver};
+
+        const MAX_ALARMS: usize = 16;
+
+        struct Alarm {
+            handle: UnsafeCell<esp_timer_handle_t>,
+            callback: AtomicU64,
+        }
+
+        impl Alarm {
+            const fn new() -> Self {
+                Self {
+                    handle: UnsafeCell::new(core::ptr::null_mut()),
+                    callback: AtomicU64::new(0),
+                }
+            }
+
+            fn handle(&self) -> esp_timer_handle_t {
+                unsafe { *self.handle.get() }
+            }
+
+            fn invoke(&self) {
+                let callback = self.callback.load(Ordering::SeqCst);
+
+                if callback != 0 {
+                    let func: fn(*mut ()) =
+                        unsafe { core::mem::transmute((callback >> 32) as usize) };
+                    let ctx = (callback & 0xffffffff) as usize as *mut ();
+
+                    func(ctx);
+                }
+            }
+        }
+
+        unsafe impl Send for Alarm {}
+        unsafe impl Sync for Alarm {}
+
+        /// An `embassy-time` driver backed by the ESP-IDF `esp_timer` service.
+        ///
+        /// Each allocated alarm is mapped to its own `esp_timer` instance, dispatched
+        /// from the timer ISR when `CONFIG_ESP_TIMER_SUPPORTS_ISR_DISPATCH_METHOD` is enabled,
+        /// and from the `esp_timer` task otherwise.
+        pub struct EspTimerDriver {
+            alarms: [Alarm; MAX_ALARMS],
+            allocated: AtomicU8,
+        }
+
+        impl EspTimerDriver {
+            #[allow(clippy::declare_interior_mutable_const)]
+            const ALARM: Alarm = Alarm::new();
+
+            const fn new() -> Self {
+                Self {
+                    alarms: [Self::ALARM; MAX_ALARMS],
+                    allocated: AtomicU8::new(0),
+                }
+            }
+
+            extern "C" fn handle(arg: *mut c_types::c_void) {
+                let alarm = &DRIVER.alarms[arg as usize];
+
+                if crate::interrupt::active() {
+                    #[cfg(esp_idf_esp_timer_supports_isr_dispatch_method)]
+                    {
+                        let signaled = crate::interrupt::with_isr_yield_signal(|| alarm.invoke());
+
+                        if signaled {
+                            unsafe {
+                                esp_timer_isr_dispatch_need_yield();
+                            }
+                        }
+                    }
+                } else {
+                    alarm.invoke();
+                }
+            }
+        }
+
+        unsafe impl Send for EspTimerDriver {}
+        unsafe impl Sync for EspTimerDriver {}
+
+        impl Driver for EspTimerDriver {
+            fn now(&self) -> u64 {
+                unsafe { esp_timer_get_time() as _ }
+            }
+
+            unsafe fn allocate_alarm(&self) -> Option<AlarmHandle> {
+                let id = self
+                    .allocated
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |allocated| {
+                        if (allocated as usize) < MAX_ALARMS {
+                            Some(allocated + 1)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok()?;
+
+                #[cfg(esp_idf_esp_timer_supports_isr_dispatch_method)]
+                let dispatch_method = esp_timer_dispatch_t_ESP_TIMER_ISR;
+
+                #[cfg(not(esp_idf_esp_timer_supports_isr_dispatch_method))]
+                let dispatch_method = esp_timer_dispatch_t_ESP_TIMER_TASK;
+
+                esp!(esp_timer_create(
+                    &esp_timer_create_args_t {
+                        callback: Some(Self::handle),
+                        name: b"embassy-time\0" as *const _ as *const _,
+                        arg: id as usize as *mut _,
+                        dispatch_method,
+                        skip_unhandled_events: false,
+                    },
+                    self.alarms[id as usize].handle.get(),
+                ))
+                .unwrap();
+
+                Some(AlarmHandle::new(id))
+            }
+
+            fn set_alarm_callback(&self, alarm: AlarmHandle, callback: fn(*mut ()), ctx: *mut ()) {
+                let value = ((callback as usize as u64) << 32) | (ctx as usize as u64);
+
+                self.alarms[alarm.id() as usize]
+                    .callback
+                    .store(value, Ordering::SeqCst);
+            }
+
+            fn set_alarm(&self, alarm: AlarmHandle, timestamp: u64) -> bool {
+                let handle = self.alarms[alarm.id() as usize].handle();
+
+                // Might fail if the timer is not running, which is fine
+                unsafe { esp_timer_stop(handle) };
+
+                let now = self.now();
+
+                if timestamp <= now {
+                    false
+                } else {
+                    esp!(unsafe { esp_timer_start_once(handle, timestamp - now) }).unwrap();
+
+                    true
+                }
+            }
+        }
+
+        pub fn link() -> i32 {
+            42
+        }
+
+        embassy_time::time_driver_impl!(static DRIVER: EspTimerDriver = EspTimerDriver::new());
+    }
+}
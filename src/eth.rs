@@ -0,0 +1,762 @@
+//! Ethernet driver
+//!
+//! `EthDriver` drives either
+//! - the internal EMAC of the ESP32 with an external RMII PHY (`EthDriver::new_rmii`),
+//! - the OpenETH MAC emulated by QEMU (`EthDriver::new_openeth`), or
+//! - an SPI Ethernet module - W5500, DM9051 or KSZ8851SNL - sharing the bus of a
+//!   `SpiMasterDriver` (`EthDriver::new_spi`).
+//!
+//! The driver only moves raw Ethernet frames: they are sent with `EthDriver::send` and
+//! received by the callback installed with `EthDriver::set_rx_callback`. Link changes are
+//! reported to the callback installed with `EthDriver::subscribe`. Attaching the driver to a
+//! TCP/IP stack is left to higher level crates, which can use `EthDriver::handle`.
+//!
+//! # Examples
+//!
+//! ```
+//! use esp_idf_hal::eth::{config, EthDriver, RmiiClockConfig, RmiiEthChipset};
+//! use esp_idf_hal::peripheral::Peripheral;
+//! use esp_idf_hal::peripherals::Peripherals;
+//!
+//! let peripherals = Peripherals::take().unwrap();
+//! let pins = peripherals.pins;
+//!
+//! let mut eth = EthDriver::new_rmii(
+//!     peripherals.mac,
+//!     pins.gpio25,
+//!     pins.gpio26,
+//!     pins.gpio27,
+//!     pins.gpio23,
+//!     pins.gpio22,
+//!     pins.gpio21,
+//!     pins.gpio19,
+//!     pins.gpio18,
+//!     RmiiClockConfig::Input(pins.gpio0.into_ref()),
+//!     Some(pins.gpio5),
+//!     RmiiEthChipset::Ip101,
+//!     None,
+//!     &config::Config::new(),
+//! )?;
+//!
+//! eth.set_rx_callback(|frame| println!("Received {} bytes", frame.len()))?;
+//! eth.start()?;
+//! ```
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+
+use core::marker::PhantomData;
+use core::ptr;
+
+use esp_idf_sys::*;
+
+#[cfg(all(esp32, esp_idf_eth_use_esp32_emac))]
+use crate::gpio;
+#[cfg(any(
+    all(esp32, esp_idf_eth_use_esp32_emac),
+    esp_idf_eth_spi_ethernet_w5500,
+    esp_idf_eth_spi_ethernet_dm9051,
+    esp_idf_eth_spi_ethernet_ksz8851snl
+))]
+use crate::gpio::{InputPin, OutputPin};
+#[cfg(any(all(esp32, esp_idf_eth_use_esp32_emac), esp_idf_eth_use_openeth))]
+use crate::mac::MAC;
+use crate::peripheral::Peripheral;
+#[cfg(all(esp32, esp_idf_eth_use_esp32_emac))]
+use crate::peripheral::PeripheralRef;
+#[cfg(any(
+    esp_idf_eth_spi_ethernet_w5500,
+    esp_idf_eth_spi_ethernet_dm9051,
+    esp_idf_eth_spi_ethernet_ksz8851snl
+))]
+use crate::spi::{Spi, SpiMasterDriver};
+
+pub mod config {
+    use core::time::Duration;
+
+    use crate::units::*;
+
+    /// Ethernet driver configuration
+    #[derive(Copy, Clone, Debug)]
+    pub struct Config {
+        pub rx_task_stack_size: usize,
+        pub rx_task_priority: u32,
+        pub check_link_period: Duration,
+        pub phy_reset_timeout: Duration,
+        pub autonego_timeout: Duration,
+        pub spi_baudrate: Hertz,
+    }
+
+    impl Config {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        /// Stack size of the task the received frames are dispatched from
+        #[must_use]
+        pub fn rx_task_stack_size(mut self, rx_task_stack_size: usize) -> Self {
+            self.rx_task_stack_size = rx_task_stack_size;
+            self
+        }
+
+        #[must_use]
+        pub fn rx_task_priority(mut self, rx_task_priority: u32) -> Self {
+            self.rx_task_priority = rx_task_priority;
+            self
+        }
+
+        /// How often the PHY is polled for link changes
+        #[must_use]
+        pub fn check_link_period(mut self, check_link_period: Duration) -> Self {
+            self.check_link_period = check_link_period;
+            self
+        }
+
+        #[must_use]
+        pub fn phy_reset_timeout(mut self, phy_reset_timeout: Duration) -> Self {
+            self.phy_reset_timeout = phy_reset_timeout;
+            self
+        }
+
+        #[must_use]
+        pub fn autonego_timeout(mut self, autonego_timeout: Duration) -> Self {
+            self.autonego_timeout = autonego_timeout;
+            self
+        }
+
+        /// SPI clock of the Ethernet module (SPI modules only)
+        #[must_use]
+        pub fn spi_baudrate(mut self, spi_baudrate: Hertz) -> Self {
+            self.spi_baudrate = spi_baudrate;
+            self
+        }
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                rx_task_stack_size: 4096,
+                rx_task_priority: 15,
+                check_link_period: Duration::from_millis(2000),
+                phy_reset_timeout: Duration::from_millis(100),
+                autonego_timeout: Duration::from_millis(4000),
+                spi_baudrate: Hertz(20_000_000),
+            }
+        }
+    }
+}
+
+/// The PHY connected to the RMII interface of the EMAC
+#[cfg(all(esp32, esp_idf_eth_use_esp32_emac))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RmiiEthChipset {
+    Ip101,
+    Rtl8201,
+    Lan87xx,
+    Dp83848,
+    Ksz80xx,
+}
+
+/// Source of the 50MHz RMII reference clock
+///
+/// The clock is either provided by the PHY or an external oscillator on GPIO0,
+/// or generated by the ESP32 on GPIO0 (APLL), GPIO16 or GPIO17 (inverted).
+#[cfg(all(esp32, esp_idf_eth_use_esp32_emac))]
+pub enum RmiiClockConfig<'d> {
+    Input(PeripheralRef<'d, gpio::Gpio0>),
+    OutputGpio0(PeripheralRef<'d, gpio::Gpio0>),
+    OutputGpio16(PeripheralRef<'d, gpio::Gpio16>),
+    OutputInvertedGpio17(PeripheralRef<'d, gpio::Gpio17>),
+}
+
+#[cfg(all(esp32, esp_idf_eth_use_esp32_emac))]
+impl<'d> RmiiClockConfig<'d> {
+    fn mode(&self) -> emac_rmii_clock_mode_t {
+        match self {
+            Self::Input(_) => emac_rmii_clock_mode_t_EMAC_CLK_EXT_IN,
+            _ => emac_rmii_clock_mode_t_EMAC_CLK_OUT,
+        }
+    }
+
+    fn gpio(&self) -> emac_rmii_clock_gpio_t {
+        match self {
+            Self::Input(_) => emac_rmii_clock_gpio_t_EMAC_CLK_IN_GPIO,
+            Self::OutputGpio0(_) => emac_rmii_clock_gpio_t_EMAC_APPL_CLK_OUT_GPIO,
+            Self::OutputGpio16(_) => emac_rmii_clock_gpio_t_EMAC_CLK_OUT_GPIO,
+            Self::OutputInvertedGpio17(_) => emac_rmii_clock_gpio_t_EMAC_CLK_OUT_180_GPIO,
+        }
+    }
+}
+
+/// An SPI Ethernet module, MAC and PHY in one chip
+#[cfg(any(
+    esp_idf_eth_spi_ethernet_w5500,
+    esp_idf_eth_spi_ethernet_dm9051,
+    esp_idf_eth_spi_ethernet_ksz8851snl
+))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SpiEthChipset {
+    #[cfg(esp_idf_eth_spi_ethernet_w5500)]
+    W5500,
+    #[cfg(esp_idf_eth_spi_ethernet_dm9051)]
+    Dm9051,
+    #[cfg(esp_idf_eth_spi_ethernet_ksz8851snl)]
+    Ksz8851Snl,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EthEvent {
+    Started,
+    Stopped,
+    Connected,
+    Disconnected,
+}
+
+impl EthEvent {
+    fn from_id(id: i32) -> Option<Self> {
+        #[allow(non_upper_case_globals)]
+        match id as eth_event_t {
+            eth_event_t_ETHERNET_EVENT_START => Some(Self::Started),
+            eth_event_t_ETHERNET_EVENT_STOP => Some(Self::Stopped),
+            eth_event_t_ETHERNET_EVENT_CONNECTED => Some(Self::Connected),
+            eth_event_t_ETHERNET_EVENT_DISCONNECTED => Some(Self::Disconnected),
+            _ => None,
+        }
+    }
+}
+
+struct Subscription {
+    handle: esp_eth_handle_t,
+    callback: Box<dyn FnMut(EthEvent) + Send + 'static>,
+}
+
+pub struct EthDriver<'d> {
+    handle: esp_eth_handle_t,
+    mac: *mut esp_eth_mac_t,
+    phy: *mut esp_eth_phy_t,
+    started: bool,
+    _rx_callback: Option<Box<Box<dyn FnMut(&[u8]) + Send + 'static>>>,
+    subscription: Option<(esp_event_handler_instance_t, Box<Subscription>)>,
+    _p: PhantomData<&'d mut ()>,
+}
+
+#[cfg(all(esp32, esp_idf_eth_use_esp32_emac))]
+impl<'d> EthDriver<'d> {
+    /// Creates a driver for the internal EMAC and an RMII PHY
+    ///
+    /// The RMII data pins are fixed; MDC and MDIO (the SMI bus the PHY registers are accessed
+    /// through) can be any pins. `phy_addr` is probed for if `None`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_rmii(
+        _mac: impl Peripheral<P = MAC> + 'd,
+        _rmii_rxd0: impl Peripheral<P = gpio::Gpio25> + 'd,
+        _rmii_rxd1: impl Peripheral<P = gpio::Gpio26> + 'd,
+        _rmii_crs_dv: impl Peripheral<P = gpio::Gpio27> + 'd,
+        rmii_mdc: impl Peripheral<P = impl OutputPin> + 'd,
+        _rmii_txd1: impl Peripheral<P = gpio::Gpio22> + 'd,
+        _rmii_tx_en: impl Peripheral<P = gpio::Gpio21> + 'd,
+        _rmii_txd0: impl Peripheral<P = gpio::Gpio19> + 'd,
+        rmii_mdio: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        rmii_ref_clk_config: RmiiClockConfig<'d>,
+        rst: Option<impl Peripheral<P = impl OutputPin> + 'd>,
+        chipset: RmiiEthChipset,
+        phy_addr: Option<u32>,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(rmii_mdc, rmii_mdio);
+
+        let rst = rst.map(|rst| rst.into_ref());
+
+        #[allow(unused_mut)]
+        let mut mac_config = Self::mac_config(config);
+
+        #[cfg(esp_idf_version_major = "4")]
+        let emac = {
+            mac_config.smi_mdc_gpio_num = rmii_mdc.pin();
+            mac_config.smi_mdio_gpio_num = rmii_mdio.pin();
+
+            // ESP-IDF 4.3 only supports configuring the RMII clock via menuconfig
+            #[cfg(not(esp_idf_version = "4.3"))]
+            {
+                mac_config.interface = eth_data_interface_t_EMAC_DATA_INTERFACE_RMII;
+                mac_config.clock_config = eth_mac_clock_config_t {
+                    rmii: eth_mac_clock_config_t__bindgen_ty_2 {
+                        clock_mode: rmii_ref_clk_config.mode(),
+                        clock_gpio: rmii_ref_clk_config.gpio(),
+                    },
+                };
+            }
+
+            #[cfg(esp_idf_version = "4.3")]
+            let _ = rmii_ref_clk_config;
+
+            unsafe { esp_eth_mac_new_esp32(&mac_config) }
+        };
+
+        #[cfg(not(esp_idf_version_major = "4"))]
+        let emac = {
+            let emac_config = eth_esp32_emac_config_t {
+                smi_mdc_gpio_num: rmii_mdc.pin(),
+                smi_mdio_gpio_num: rmii_mdio.pin(),
+                interface: eth_data_interface_t_EMAC_DATA_INTERFACE_RMII,
+                clock_config: eth_mac_clock_config_t {
+                    rmii: eth_mac_clock_config_t__bindgen_ty_2 {
+                        clock_mode: rmii_ref_clk_config.mode(),
+                        clock_gpio: rmii_ref_clk_config.gpio(),
+                    },
+                },
+                dma_burst_len: eth_mac_dma_burst_len_t_ETH_DMA_BURST_LEN_32,
+                ..Default::default()
+            };
+
+            unsafe { esp_eth_mac_new_esp32(&emac_config, &mac_config) }
+        };
+
+        if emac.is_null() {
+            return Err(EspError::from(ESP_FAIL).unwrap());
+        }
+
+        let phy_config = Self::phy_config(phy_addr, rst.as_ref().map(|p| p.pin()), config);
+
+        let phy = unsafe {
+            match chipset {
+                RmiiEthChipset::Ip101 => esp_eth_phy_new_ip101(&phy_config),
+                RmiiEthChipset::Rtl8201 => esp_eth_phy_new_rtl8201(&phy_config),
+                #[cfg(esp_idf_version = "4.3")]
+                RmiiEthChipset::Lan87xx => esp_eth_phy_new_lan8720(&phy_config),
+                #[cfg(not(esp_idf_version = "4.3"))]
+                RmiiEthChipset::Lan87xx => esp_eth_phy_new_lan87xx(&phy_config),
+                RmiiEthChipset::Dp83848 => esp_eth_phy_new_dp83848(&phy_config),
+                #[cfg(esp_idf_version_major = "4")]
+                RmiiEthChipset::Ksz80xx => esp_eth_phy_new_ksz8041(&phy_config),
+                #[cfg(not(esp_idf_version_major = "4"))]
+                RmiiEthChipset::Ksz80xx => esp_eth_phy_new_ksz80xx(&phy_config),
+            }
+        };
+
+        Self::init(emac, phy, config)
+    }
+}
+
+#[cfg(esp_idf_eth_use_openeth)]
+impl<'d> EthDriver<'d> {
+    /// Creates a driver for the OpenETH MAC emulated by QEMU
+    pub fn new_openeth(
+        _mac: impl Peripheral<P = MAC> + 'd,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        let mac_config = Self::mac_config(config);
+        let emac = unsafe { esp_eth_mac_new_openeth(&mac_config) };
+
+        if emac.is_null() {
+            return Err(EspError::from(ESP_FAIL).unwrap());
+        }
+
+        let phy_config = Self::phy_config(None, None, config);
+        let phy = unsafe { esp_eth_phy_new_dp83848(&phy_config) };
+
+        Self::init(emac, phy, config)
+    }
+}
+
+#[cfg(any(
+    esp_idf_eth_spi_ethernet_w5500,
+    esp_idf_eth_spi_ethernet_dm9051,
+    esp_idf_eth_spi_ethernet_ksz8851snl
+))]
+impl<'d> EthDriver<'d> {
+    /// Creates a driver for an SPI Ethernet module
+    ///
+    /// The module is added as another device to the bus of `spi`, with its own CS pin.
+    /// As frames are transferred in one go, the `SpiMasterDriver` must have been created
+    /// with DMA enabled.
+    ///
+    /// These modules have no MAC address of their own, so the one ESP-IDF derives for Ethernet
+    /// from the eFuse base MAC address is assigned; use `EthDriver::set_mac_addr` to change it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_spi<SPI: Spi>(
+        _spi: &'d SpiMasterDriver<'d, SPI>,
+        int: impl Peripheral<P = impl InputPin> + 'd,
+        cs: impl Peripheral<P = impl OutputPin> + 'd,
+        rst: Option<impl Peripheral<P = impl OutputPin> + 'd>,
+        chipset: SpiEthChipset,
+        phy_addr: Option<u32>,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(int, cs);
+
+        let rst = rst.map(|rst| rst.into_ref());
+
+        // The modules signal received frames on the INT pin, via the GPIO ISR service
+        crate::gpio::enable_isr_service()?;
+
+        let (command_bits, address_bits) = match chipset {
+            #[cfg(esp_idf_eth_spi_ethernet_w5500)]
+            SpiEthChipset::W5500 => (16, 8),
+            #[cfg(esp_idf_eth_spi_ethernet_dm9051)]
+            SpiEthChipset::Dm9051 => (1, 7),
+            #[cfg(esp_idf_eth_spi_ethernet_ksz8851snl)]
+            SpiEthChipset::Ksz8851Snl => (0, 0),
+        };
+
+        // Only needs to outlive the `esp_eth_mac_new_*` call, which adds the device to the bus
+        let mut device_config = spi_device_interface_config_t {
+            command_bits,
+            address_bits,
+            mode: 0,
+            clock_speed_hz: config.spi_baudrate.0 as _,
+            spics_io_num: cs.pin(),
+            queue_size: 20,
+            ..Default::default()
+        };
+
+        let mac_config = Self::mac_config(config);
+
+        let emac = unsafe {
+            match chipset {
+                #[cfg(esp_idf_eth_spi_ethernet_w5500)]
+                SpiEthChipset::W5500 => esp_eth_mac_new_w5500(
+                    &eth_w5500_config_t {
+                        spi_host_id: SPI::device(),
+                        spi_devcfg: &mut device_config,
+                        int_gpio_num: int.pin(),
+                        ..Default::default()
+                    },
+                    &mac_config,
+                ),
+                #[cfg(esp_idf_eth_spi_ethernet_dm9051)]
+                SpiEthChipset::Dm9051 => esp_eth_mac_new_dm9051(
+                    &eth_dm9051_config_t {
+                        spi_host_id: SPI::device(),
+                        spi_devcfg: &mut device_config,
+                        int_gpio_num: int.pin(),
+                        ..Default::default()
+                    },
+                    &mac_config,
+                ),
+                #[cfg(esp_idf_eth_spi_ethernet_ksz8851snl)]
+                SpiEthChipset::Ksz8851Snl => esp_eth_mac_new_ksz8851snl(
+                    &eth_ksz8851snl_config_t {
+                        spi_host_id: SPI::device(),
+                        spi_devcfg: &mut device_config,
+                        int_gpio_num: int.pin(),
+                        ..Default::default()
+                    },
+                    &mac_config,
+                ),
+            }
+        };
+
+        if emac.is_null() {
+            return Err(EspError::from(ESP_FAIL).unwrap());
+        }
+
+        let phy_config = Self::phy_config(phy_addr, rst.as_ref().map(|p| p.pin()), config);
+
+        let phy = unsafe {
+            match chipset {
+                #[cfg(esp_idf_eth_spi_ethernet_w5500)]
+                SpiEthChipset::W5500 => esp_eth_phy_new_w5500(&phy_config),
+                #[cfg(esp_idf_eth_spi_ethernet_dm9051)]
+                SpiEthChipset::Dm9051 => esp_eth_phy_new_dm9051(&phy_config),
+                #[cfg(esp_idf_eth_spi_ethernet_ksz8851snl)]
+                SpiEthChipset::Ksz8851Snl => esp_eth_phy_new_ksz8851snl(&phy_config),
+            }
+        };
+
+        let mut this = Self::init(emac, phy, config)?;
+
+        let mut mac_addr = [0_u8; 6];
+        esp!(unsafe { esp_read_mac(mac_addr.as_mut_ptr(), esp_mac_type_t_ESP_MAC_ETH) })?;
+
+        this.set_mac_addr(&mac_addr)?;
+
+        Ok(this)
+    }
+}
+
+impl<'d> EthDriver<'d> {
+    fn mac_config(config: &config::Config) -> eth_mac_config_t {
+        eth_mac_config_t {
+            sw_reset_timeout_ms: 100,
+            rx_task_stack_size: config.rx_task_stack_size as _,
+            rx_task_prio: config.rx_task_priority as _,
+            ..Default::default()
+        }
+    }
+
+    fn phy_config(
+        phy_addr: Option<u32>,
+        reset_pin: Option<i32>,
+        config: &config::Config,
+    ) -> eth_phy_config_t {
+        eth_phy_config_t {
+            phy_addr: phy_addr.map_or(ESP_ETH_PHY_ADDR_AUTO, |addr| addr as _),
+            reset_timeout_ms: config.phy_reset_timeout.as_millis() as _,
+            autonego_timeout_ms: config.autonego_timeout.as_millis() as _,
+            reset_gpio_num: reset_pin.unwrap_or(-1),
+        }
+    }
+
+    fn init(
+        mac: *mut esp_eth_mac_t,
+        phy: *mut esp_eth_phy_t,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        if phy.is_null() {
+            unsafe { Self::delete(mac, phy) };
+
+            return Err(EspError::from(ESP_FAIL).unwrap());
+        }
+
+        let eth_config = esp_eth_config_t {
+            mac,
+            phy,
+            check_link_period_ms: config.check_link_period.as_millis() as _,
+            ..Default::default()
+        };
+
+        let mut handle: esp_eth_handle_t = ptr::null_mut();
+
+        if let Err(err) = esp!(unsafe { esp_eth_driver_install(&eth_config, &mut handle) }) {
+            unsafe { Self::delete(mac, phy) };
+
+            return Err(err);
+        }
+
+        Ok(Self {
+            handle,
+            mac,
+            phy,
+            started: false,
+            _rx_callback: None,
+            subscription: None,
+            _p: PhantomData,
+        })
+    }
+
+    unsafe fn delete(mac: *mut esp_eth_mac_t, phy: *mut esp_eth_phy_t) {
+        if let Some(phy) = phy.as_mut() {
+            esp!(phy.del.unwrap()(phy)).unwrap();
+        }
+
+        if let Some(mac) = mac.as_mut() {
+            esp!(mac.del.unwrap()(mac)).unwrap();
+        }
+    }
+
+    /// The ESP-IDF driver handle, e.g. for attaching the driver to `esp_netif`
+    pub fn handle(&self) -> esp_eth_handle_t {
+        self.handle
+    }
+
+    pub fn start(&mut self) -> Result<(), EspError> {
+        if !self.started {
+            esp!(unsafe { esp_eth_start(self.handle) })?;
+
+            self.started = true;
+        }
+
+        Ok(())
+    }
+
+    pub fn stop(&mut self) -> Result<(), EspError> {
+        if self.started {
+            esp!(unsafe { esp_eth_stop(self.handle) })?;
+
+            self.started = false;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Sends an Ethernet frame, destination and source MAC addresses included
+    pub fn send(&mut self, frame: &[u8]) -> Result<(), EspError> {
+        esp!(unsafe { esp_eth_transmit(self.handle, frame.as_ptr() as *mut _, frame.len() as _) })
+    }
+
+    /// Sets the callback received frames are passed to
+    ///
+    /// The callback is called from the receive task of the driver.
+    /// Fails with `ESP_ERR_INVALID_STATE` if the driver is started.
+    pub fn set_rx_callback<F>(&mut self, callback: F) -> Result<(), EspError>
+    where
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        if self.started {
+            return Err(EspError::from(ESP_ERR_INVALID_STATE).unwrap());
+        }
+
+        let mut callback: Box<Box<dyn FnMut(&[u8]) + Send + 'static>> =
+            Box::new(Box::new(callback));
+
+        esp!(unsafe {
+            esp_eth_update_input_path(
+                self.handle,
+                Some(Self::handle_rx),
+                callback.as_mut() as *mut _ as *mut _,
+            )
+        })?;
+
+        self._rx_callback = Some(callback);
+
+        Ok(())
+    }
+
+    /// Subscribes `callback` to the start, stop and link events of the driver
+    ///
+    /// The events are delivered through the default ESP-IDF event loop, which must have been
+    /// created beforehand; the callback is called from the event loop task.
+    pub fn subscribe<F>(&mut self, callback: F) -> Result<(), EspError>
+    where
+        F: FnMut(EthEvent) + Send + 'static,
+    {
+        self.unsubscribe()?;
+
+        let mut subscription = Box::new(Subscription {
+            handle: self.handle,
+            callback: Box::new(callback),
+        });
+
+        let mut instance: esp_event_handler_instance_t = ptr::null_mut();
+
+        esp!(unsafe {
+            esp_event_handler_instance_register(
+                ETH_EVENT,
+                ESP_EVENT_ANY_ID,
+                Some(Self::handle_event),
+                subscription.as_mut() as *mut _ as *mut _,
+                &mut instance,
+            )
+        })?;
+
+        self.subscription = Some((instance, subscription));
+
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self) -> Result<(), EspError> {
+        if let Some((instance, _subscription)) = self.subscription.take() {
+            esp!(unsafe {
+                esp_event_handler_instance_unregister(ETH_EVENT, ESP_EVENT_ANY_ID, instance)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    pub fn mac_addr(&self) -> Result<[u8; 6], EspError> {
+        let mut mac_addr = [0_u8; 6];
+
+        esp!(unsafe {
+            esp_eth_ioctl(
+                self.handle,
+                esp_eth_io_cmd_t_ETH_CMD_G_MAC_ADDR,
+                mac_addr.as_mut_ptr() as *mut _,
+            )
+        })?;
+
+        Ok(mac_addr)
+    }
+
+    pub fn set_mac_addr(&mut self, mac_addr: &[u8; 6]) -> Result<(), EspError> {
+        esp!(unsafe {
+            esp_eth_ioctl(
+                self.handle,
+                esp_eth_io_cmd_t_ETH_CMD_S_MAC_ADDR,
+                mac_addr.as_ptr() as *mut _,
+            )
+        })
+    }
+
+    /// The address of the PHY on the SMI bus, as configured or probed
+    pub fn phy_addr(&self) -> Result<u32, EspError> {
+        let mut phy_addr: u32 = 0;
+
+        esp!(unsafe {
+            esp_eth_ioctl(
+                self.handle,
+                esp_eth_io_cmd_t_ETH_CMD_G_PHY_ADDR,
+                &mut phy_addr as *mut _ as *mut _,
+            )
+        })?;
+
+        Ok(phy_addr)
+    }
+
+    /// Reads a PHY register over SMI (MDIO)
+    ///
+    /// SPI modules emulate the standard registers to a varying extent.
+    pub fn read_phy_reg(&mut self, reg: u32) -> Result<u32, EspError> {
+        let phy_addr = self.phy_addr()?;
+        let mut value: u32 = 0;
+
+        let mac = unsafe { self.mac.as_mut() }.unwrap();
+
+        esp!(unsafe { mac.read_phy_reg.unwrap()(mac, phy_addr, reg, &mut value) })?;
+
+        Ok(value)
+    }
+
+    /// Writes a PHY register over SMI (MDIO)
+    pub fn write_phy_reg(&mut self, reg: u32, value: u32) -> Result<(), EspError> {
+        let phy_addr = self.phy_addr()?;
+
+        let mac = unsafe { self.mac.as_mut() }.unwrap();
+
+        esp!(unsafe { mac.write_phy_reg.unwrap()(mac, phy_addr, reg, value) })
+    }
+
+    extern "C" fn handle_rx(
+        _handle: esp_eth_handle_t,
+        buffer: *mut u8,
+        length: u32,
+        arg: *mut c_types::c_void,
+    ) -> esp_err_t {
+        let callback =
+            unsafe { (arg as *mut Box<dyn FnMut(&[u8]) + Send + 'static>).as_mut() }.unwrap();
+
+        callback(unsafe { core::slice::from_raw_parts(buffer, length as _) });
+
+        // The driver hands over the ownership of the buffer
+        unsafe { free(buffer as *mut _) };
+
+        ESP_OK
+    }
+
+    extern "C" fn handle_event(
+        arg: *mut c_types::c_void,
+        _event_base: esp_event_base_t,
+        event_id: i32,
+        event_data: *mut c_types::c_void,
+    ) {
+        let subscription = unsafe { (arg as *mut Subscription).as_mut() }.unwrap();
+
+        // The events of all Ethernet drivers are posted with the same base
+        let handle = unsafe { *(event_data as *const esp_eth_handle_t) };
+
+        if handle == subscription.handle {
+            if let Some(event) = EthEvent::from_id(event_id) {
+                (subscription.callback)(event);
+            }
+        }
+    }
+}
+
+impl<'d> Drop for EthDriver<'d> {
+    fn drop(&mut self) {
+        self.stop().unwrap();
+        self.unsubscribe().unwrap();
+
+        esp!(unsafe { esp_eth_driver_uninstall(self.handle) }).unwrap();
+
+        unsafe { Self::delete(self.mac, self.phy) };
+    }
+}
+
+unsafe impl<'d> Send for EthDriver<'d> {}
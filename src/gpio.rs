@@ -270,6 +270,28 @@ impl From<Pull> for gpio_pull_mode_t {
     }
 }
 
+/// Direction of a pin while the chip is in light sleep
+#[cfg(not(feature = "riscv-ulp-hal"))]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum SleepDirection {
+    Disabled,
+    Input,
+    Output,
+    InputOutput,
+}
+
+#[cfg(not(feature = "riscv-ulp-hal"))]
+impl From<SleepDirection> for gpio_mode_t {
+    fn from(direction: SleepDirection) -> gpio_mode_t {
+        match direction {
+            SleepDirection::Disabled => gpio_mode_t_GPIO_MODE_DISABLE,
+            SleepDirection::Input => gpio_mode_t_GPIO_MODE_INPUT,
+            SleepDirection::Output => gpio_mode_t_GPIO_MODE_OUTPUT,
+            SleepDirection::InputOutput => gpio_mode_t_GPIO_MODE_INPUT_OUTPUT,
+        }
+    }
+}
+
 /// Digital input or output level.
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Level {
@@ -314,6 +336,15 @@ pub trait OutputMode {
     const RTC: bool;
 }
 
+/// Implemented by all pin modes
+pub trait PinMode {
+    const RTC: bool;
+}
+
+/// Implemented by the RTC IO pin modes
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+pub trait RtcMode: PinMode {}
+
 pub struct Disabled;
 pub struct Input;
 pub struct Output;
@@ -327,6 +358,54 @@ pub struct RtcOutput;
 #[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
 pub struct RtcInputOutput;
 
+impl PinMode for Disabled {
+    const RTC: bool = false;
+}
+
+impl PinMode for Input {
+    const RTC: bool = false;
+}
+
+impl PinMode for Output {
+    const RTC: bool = false;
+}
+
+impl PinMode for InputOutput {
+    const RTC: bool = false;
+}
+
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+impl PinMode for RtcDisabled {
+    const RTC: bool = true;
+}
+
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+impl PinMode for RtcInput {
+    const RTC: bool = true;
+}
+
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+impl PinMode for RtcOutput {
+    const RTC: bool = true;
+}
+
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+impl PinMode for RtcInputOutput {
+    const RTC: bool = true;
+}
+
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+impl RtcMode for RtcDisabled {}
+
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+impl RtcMode for RtcInput {}
+
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+impl RtcMode for RtcOutput {}
+
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+impl RtcMode for RtcInputOutput {}
+
 impl InputMode for Input {
     const RTC: bool = false;
 }
@@ -762,6 +841,90 @@ impl<'d, T: Pin, MODE> PinDriver<'d, T, MODE> {
         Ok(())
     }
 
+    /// Latches the current state of the pin (level, direction, pulls and drive strength),
+    /// so that it is kept across a chip reset, a watchdog reset or a light sleep,
+    /// as well as a deep sleep for the RTC pins.
+    ///
+    /// While held, changes to the pin configuration or level do not take effect.
+    /// To also hold the digital (non-RTC) pins during deep sleep, call `enable_deep_sleep_hold` as well.
+    #[inline]
+    #[cfg(not(feature = "riscv-ulp-hal"))]
+    pub fn enable_hold(&mut self) -> Result<(), EspError>
+    where
+        MODE: PinMode,
+    {
+        if MODE::RTC {
+            #[cfg(not(esp32c3))]
+            esp!(unsafe { rtc_gpio_hold_en(self.pin.pin()) })?;
+
+            #[cfg(esp32c3)]
+            unreachable!();
+        } else {
+            esp!(unsafe { gpio_hold_en(self.pin.pin()) })?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    #[cfg(not(feature = "riscv-ulp-hal"))]
+    pub fn disable_hold(&mut self) -> Result<(), EspError>
+    where
+        MODE: PinMode,
+    {
+        if MODE::RTC {
+            #[cfg(not(esp32c3))]
+            esp!(unsafe { rtc_gpio_hold_dis(self.pin.pin()) })?;
+
+            #[cfg(esp32c3)]
+            unreachable!();
+        } else {
+            esp!(unsafe { gpio_hold_dis(self.pin.pin()) })?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the direction and the pulls the pin switches to while the chip is in light sleep.
+    ///
+    /// When the pin is an output during sleep, it keeps driving the level it had
+    /// before the chip went to sleep.
+    #[cfg(not(feature = "riscv-ulp-hal"))]
+    pub fn set_sleep_mode(
+        &mut self,
+        direction: SleepDirection,
+        pull: Pull,
+    ) -> Result<(), EspError> {
+        esp!(unsafe { gpio_sleep_set_direction(self.pin.pin(), direction.into()) })?;
+        esp!(unsafe { gpio_sleep_set_pull_mode(self.pin.pin(), pull.into()) })?;
+        esp!(unsafe { gpio_sleep_sel_en(self.pin.pin()) })?;
+
+        Ok(())
+    }
+
+    /// Keeps the regular configuration of the pin while the chip is in light sleep
+    #[cfg(not(feature = "riscv-ulp-hal"))]
+    pub fn clear_sleep_mode(&mut self) -> Result<(), EspError> {
+        esp!(unsafe { gpio_sleep_sel_dis(self.pin.pin()) })
+    }
+
+    /// Enables the hardware glitch filter of the pin, which filters out input pulses
+    /// shorter than two clock cycles of the filter clock source.
+    ///
+    /// The filter stays enabled for as long as the returned `PinGlitchFilter` is alive.
+    #[cfg(all(
+        not(feature = "riscv-ulp-hal"),
+        not(esp_idf_version_major = "4"),
+        not(esp_idf_version = "5.0"),
+        any(esp32c3, esp32s3)
+    ))]
+    pub fn glitch_filter(&self) -> Result<PinGlitchFilter<'_>, EspError>
+    where
+        MODE: InputMode,
+    {
+        PinGlitchFilter::new(self.pin.pin())
+    }
+
     #[inline]
     pub fn is_high(&self) -> bool
     where
@@ -927,24 +1090,7 @@ impl<'d, T: Pin, MODE> PinDriver<'d, T, MODE> {
         if MODE::RTC {
             #[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
             unsafe {
-                match pull {
-                    Pull::Down => {
-                        esp!(rtc_gpio_pulldown_en(self.pin.pin()))?;
-                        esp!(rtc_gpio_pullup_dis(self.pin.pin()))?;
-                    }
-                    Pull::Up => {
-                        esp!(rtc_gpio_pulldown_dis(self.pin.pin()))?;
-                        esp!(rtc_gpio_pullup_en(self.pin.pin()))?;
-                    }
-                    Pull::UpDown => {
-                        esp!(rtc_gpio_pulldown_en(self.pin.pin()))?;
-                        esp!(rtc_gpio_pullup_en(self.pin.pin()))?;
-                    }
-                    Pull::Floating => {
-                        esp!(rtc_gpio_pulldown_dis(self.pin.pin()))?;
-                        esp!(rtc_gpio_pullup_dis(self.pin.pin()))?;
-                    }
-                }
+                rtc_set_pull(self.pin.pin(), pull)?;
             }
 
             #[cfg(any(feature = "riscv-ulp-hal", esp32c3))]
@@ -1038,6 +1184,43 @@ impl<'d, T: Pin, MODE> PinDriver<'d, T, MODE> {
     }
 }
 
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+impl<'d, T: Pin + RTCPin, MODE: RtcMode> PinDriver<'d, T, MODE> {
+    /// Enables the pulls of the pin through the RTC IO controller, so that they remain
+    /// in effect while the digital domain is powered down
+    pub fn set_rtc_pull(&mut self, pull: Pull) -> Result<(), EspError> {
+        unsafe { rtc_set_pull(self.pin.pin(), pull) }
+    }
+
+    /// Enables the wakeup of the chip from light sleep when the pin is at `level`
+    pub fn enable_rtc_wakeup(&mut self, level: Level) -> Result<(), EspError>
+    where
+        MODE: InputMode,
+    {
+        let intr_type = match level {
+            Level::Low => gpio_int_type_t_GPIO_INTR_LOW_LEVEL,
+            Level::High => gpio_int_type_t_GPIO_INTR_HIGH_LEVEL,
+        };
+
+        esp!(unsafe { rtc_gpio_wakeup_enable(self.pin.pin(), intr_type) })
+    }
+
+    pub fn disable_rtc_wakeup(&mut self) -> Result<(), EspError>
+    where
+        MODE: InputMode,
+    {
+        esp!(unsafe { rtc_gpio_wakeup_disable(self.pin.pin()) })
+    }
+
+    /// Disconnects the pin from all internal circuits (input, output, pulls) and holds it in that state,
+    /// which avoids extra current through external pulls during deep sleep.
+    ///
+    /// Call `disable_hold` after waking up to use the pin again.
+    pub fn isolate(&mut self) -> Result<(), EspError> {
+        esp!(unsafe { rtc_gpio_isolate(self.pin.pin()) })
+    }
+}
+
 impl<'d, T: Pin, MODE> Drop for PinDriver<'d, T, MODE> {
     fn drop(&mut self) {
         unsafe { reset_pin(self.pin.pin(), gpio_mode_t_GPIO_MODE_DISABLE) }.unwrap();
@@ -1046,6 +1229,303 @@ impl<'d, T: Pin, MODE> Drop for PinDriver<'d, T, MODE> {
 
 unsafe impl<'d, T: Pin, MODE> Send for PinDriver<'d, T, MODE> {}
 
+/// Enables the hold of the digital (non-RTC) pins during deep sleep.
+///
+/// Only the pins on which `PinDriver::enable_hold` was called are held.
+#[cfg(not(feature = "riscv-ulp-hal"))]
+pub fn enable_deep_sleep_hold() {
+    unsafe { gpio_deep_sleep_hold_en() }
+}
+
+#[cfg(not(feature = "riscv-ulp-hal"))]
+pub fn disable_deep_sleep_hold() {
+    unsafe { gpio_deep_sleep_hold_dis() }
+}
+
+/// An enabled hardware glitch filter of an input pin, see `PinDriver::glitch_filter`
+#[cfg(all(
+    not(feature = "riscv-ulp-hal"),
+    not(esp_idf_version_major = "4"),
+    not(esp_idf_version = "5.0"),
+    any(esp32c3, esp32s3)
+))]
+pub struct PinGlitchFilter<'a> {
+    handle: gpio_glitch_filter_handle_t,
+    _p: PhantomData<&'a ()>,
+}
+
+#[cfg(all(
+    not(feature = "riscv-ulp-hal"),
+    not(esp_idf_version_major = "4"),
+    not(esp_idf_version = "5.0"),
+    any(esp32c3, esp32s3)
+))]
+impl<'a> PinGlitchFilter<'a> {
+    fn new(pin: i32) -> Result<Self, EspError> {
+        let config = gpio_pin_glitch_filter_config_t {
+            clk_src: soc_periph_glitch_filter_clk_src_t_GLITCH_FILTER_CLK_SRC_DEFAULT,
+            gpio_num: pin,
+        };
+
+        let mut handle: gpio_glitch_filter_handle_t = core::ptr::null_mut();
+
+        esp!(unsafe { gpio_new_pin_glitch_filter(&config, &mut handle) })?;
+
+        let filter = Self {
+            handle,
+            _p: PhantomData,
+        };
+
+        esp!(unsafe { gpio_glitch_filter_enable(filter.handle) })?;
+
+        Ok(filter)
+    }
+}
+
+#[cfg(all(
+    not(feature = "riscv-ulp-hal"),
+    not(esp_idf_version_major = "4"),
+    not(esp_idf_version = "5.0"),
+    any(esp32c3, esp32s3)
+))]
+impl<'a> Drop for PinGlitchFilter<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            // Fails if the filter did not get enabled, which is fine
+            gpio_glitch_filter_disable(self.handle);
+        }
+
+        esp!(unsafe { gpio_del_glitch_filter(self.handle) }).unwrap();
+    }
+}
+
+/// A bus of pins written and read together as a single value, e.g. the data lines of
+/// an 8080-style LCD interface or the segment lines of a 7-segment display
+pub trait ParallelBus {
+    type Error;
+
+    /// Number of bits (pins) of the bus
+    fn width(&self) -> usize;
+
+    /// Drives the bus to `value`; bit `n` of `value` corresponds to the `n`-th pin of the bus
+    fn write(&mut self, value: u32) -> Result<(), Self::Error>;
+}
+
+/// A `ParallelBus` which can also sample its pins
+pub trait ParallelBusRead: ParallelBus {
+    fn read(&mut self) -> Result<u32, Self::Error>;
+}
+
+#[cfg(not(feature = "riscv-ulp-hal"))]
+mod regs {
+    #[cfg(esp32)]
+    const GPIO_BASE: usize = 0x3ff4_4000;
+    #[cfg(esp32s2)]
+    const GPIO_BASE: usize = 0x3f40_4000;
+    #[cfg(any(esp32s3, esp32c3))]
+    const GPIO_BASE: usize = 0x6000_4000;
+
+    pub const OUT: [usize; 2] = [GPIO_BASE + 0x04, GPIO_BASE + 0x10];
+    pub const OUT_W1TS: [usize; 2] = [GPIO_BASE + 0x08, GPIO_BASE + 0x14];
+    pub const OUT_W1TC: [usize; 2] = [GPIO_BASE + 0x0c, GPIO_BASE + 0x18];
+    pub const IN: [usize; 2] = [GPIO_BASE + 0x3c, GPIO_BASE + 0x40];
+
+    #[inline(always)]
+    pub fn read(reg: usize) -> u32 {
+        unsafe { core::ptr::read_volatile(reg as *const u32) }
+    }
+
+    #[inline(always)]
+    pub fn write(reg: usize, value: u32) {
+        unsafe { core::ptr::write_volatile(reg as *mut u32, value) }
+    }
+}
+
+/// `N` pins driven and sampled as one `N`-bit value.
+///
+/// Writes only touch the `GPIO.out_w1ts` / `GPIO.out_w1tc` registers of the pin banks
+/// the group spans, so all pins of a bank change their level at (almost) the same time:
+/// the pins going high switch first, immediately followed by the pins going low.
+#[cfg(not(feature = "riscv-ulp-hal"))]
+pub struct PinGroup<'d, MODE, const N: usize> {
+    pins: [i32; N],
+    _p: PhantomData<&'d mut ()>,
+    _mode: PhantomData<MODE>,
+}
+
+#[cfg(not(feature = "riscv-ulp-hal"))]
+impl<'d, const N: usize> PinGroup<'d, Output, N> {
+    pub fn output(pins: [PeripheralRef<'d, AnyOutputPin>; N]) -> Result<Self, EspError> {
+        Self::new(pins.map(|pin| pin.pin()), gpio_mode_t_GPIO_MODE_OUTPUT)
+    }
+}
+
+#[cfg(not(feature = "riscv-ulp-hal"))]
+impl<'d, const N: usize> PinGroup<'d, InputOutput, N> {
+    pub fn input_output(pins: [PeripheralRef<'d, AnyIOPin>; N]) -> Result<Self, EspError> {
+        Self::new(
+            pins.map(|pin| pin.pin()),
+            gpio_mode_t_GPIO_MODE_INPUT_OUTPUT,
+        )
+    }
+}
+
+#[cfg(not(feature = "riscv-ulp-hal"))]
+impl<'d, MODE, const N: usize> PinGroup<'d, MODE, N> {
+    fn new(pins: [i32; N], mode: gpio_mode_t) -> Result<Self, EspError> {
+        if N > 32 {
+            return Err(EspError::from(ESP_ERR_INVALID_ARG).unwrap());
+        }
+
+        for pin in pins {
+            unsafe { reset_pin(pin, mode) }?;
+        }
+
+        Ok(Self {
+            pins,
+            _p: PhantomData,
+            _mode: PhantomData,
+        })
+    }
+
+    /// Returns the GPIO numbers of the pins in the group
+    pub fn pins(&self) -> &[i32; N] {
+        &self.pins
+    }
+
+    /// Drives the pins to `value`; bit `n` of `value` corresponds to the `n`-th pin of the group
+    #[inline]
+    pub fn set_value(&mut self, value: u32)
+    where
+        MODE: OutputMode,
+    {
+        let mut set = [0_u32; 2];
+        let mut clear = [0_u32; 2];
+
+        for (index, pin) in self.pins.iter().enumerate() {
+            let bank = (*pin / 32) as usize;
+            let mask = 1 << (*pin % 32);
+
+            if value & (1 << index) != 0 {
+                set[bank] |= mask;
+            } else {
+                clear[bank] |= mask;
+            }
+        }
+
+        for bank in 0..2 {
+            if set[bank] != 0 {
+                regs::write(regs::OUT_W1TS[bank], set[bank]);
+            }
+
+            if clear[bank] != 0 {
+                regs::write(regs::OUT_W1TC[bank], clear[bank]);
+            }
+        }
+    }
+
+    /// Returns the value the pins are driven to
+    #[inline]
+    pub fn get_output_value(&self) -> u32
+    where
+        MODE: OutputMode,
+    {
+        Self::gather(&self.pins, regs::OUT)
+    }
+
+    /// Returns the value sampled from the pins
+    #[inline]
+    pub fn get_value(&self) -> u32
+    where
+        MODE: InputMode,
+    {
+        Self::gather(&self.pins, regs::IN)
+    }
+
+    fn gather(pins: &[i32; N], registers: [usize; 2]) -> u32 {
+        let banks = [
+            regs::read(registers[0]),
+            if pins.iter().any(|pin| *pin >= 32) {
+                regs::read(registers[1])
+            } else {
+                0
+            },
+        ];
+
+        pins.iter().enumerate().fold(0, |value, (index, pin)| {
+            if banks[(*pin / 32) as usize] & (1 << (*pin % 32)) != 0 {
+                value | (1 << index)
+            } else {
+                value
+            }
+        })
+    }
+}
+
+#[cfg(not(feature = "riscv-ulp-hal"))]
+impl<'d, MODE, const N: usize> Drop for PinGroup<'d, MODE, N> {
+    fn drop(&mut self) {
+        for pin in self.pins {
+            unsafe { reset_pin(pin, gpio_mode_t_GPIO_MODE_DISABLE) }.unwrap();
+        }
+    }
+}
+
+#[cfg(not(feature = "riscv-ulp-hal"))]
+unsafe impl<'d, MODE, const N: usize> Send for PinGroup<'d, MODE, N> {}
+
+#[cfg(not(feature = "riscv-ulp-hal"))]
+impl<'d, MODE, const N: usize> ParallelBus for PinGroup<'d, MODE, N>
+where
+    MODE: OutputMode,
+{
+    type Error = core::convert::Infallible;
+
+    fn width(&self) -> usize {
+        N
+    }
+
+    fn write(&mut self, value: u32) -> Result<(), Self::Error> {
+        self.set_value(value);
+
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "riscv-ulp-hal"))]
+impl<'d, MODE, const N: usize> ParallelBusRead for PinGroup<'d, MODE, N>
+where
+    MODE: InputMode + OutputMode,
+{
+    fn read(&mut self) -> Result<u32, Self::Error> {
+        Ok(self.get_value())
+    }
+}
+
+#[cfg(all(not(feature = "riscv-ulp-hal"), not(esp32c3)))]
+unsafe fn rtc_set_pull(pin: i32, pull: Pull) -> Result<(), EspError> {
+    match pull {
+        Pull::Down => {
+            esp!(rtc_gpio_pulldown_en(pin))?;
+            esp!(rtc_gpio_pullup_dis(pin))?;
+        }
+        Pull::Up => {
+            esp!(rtc_gpio_pulldown_dis(pin))?;
+            esp!(rtc_gpio_pullup_en(pin))?;
+        }
+        Pull::UpDown => {
+            esp!(rtc_gpio_pulldown_en(pin))?;
+            esp!(rtc_gpio_pullup_en(pin))?;
+        }
+        Pull::Floating => {
+            esp!(rtc_gpio_pulldown_dis(pin))?;
+            esp!(rtc_gpio_pullup_dis(pin))?;
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(not(feature = "riscv-ulp-hal"))]
 pub(crate) unsafe fn rtc_reset_pin(pin: i32) -> Result<(), EspError> {
     reset_pin(pin, gpio_mode_t_GPIO_MODE_DISABLE)?;
@@ -1228,14 +1708,36 @@ static ISR_SERVICE_ENABLED: core::sync::atomic::AtomicBool =
 static ISR_SERVICE_ENABLED_CS: crate::task::CriticalSection = crate::task::CriticalSection::new();
 
 #[cfg(all(not(feature = "riscv-ulp-hal"), feature = "alloc"))]
-fn enable_isr_service() -> Result<(), EspError> {
+static ISR_ALLOC_FLAGS: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(0);
+
+/// Sets the interrupt allocation flags used for the GPIO ISR service.
+///
+/// The ISR service is installed lazily, when the first pin subscribes to interrupts,
+/// so this function needs to be called before that, or else it fails with `ESP_ERR_INVALID_STATE`.
+#[cfg(all(not(feature = "riscv-ulp-hal"), feature = "alloc"))]
+pub fn init_isr_alloc_flags(interrupt: crate::interrupt::InterruptConfig) -> Result<(), EspError> {
+    use core::sync::atomic::Ordering;
+
+    let _guard = ISR_SERVICE_ENABLED_CS.enter();
+
+    if ISR_SERVICE_ENABLED.load(Ordering::SeqCst) {
+        return Err(EspError::from(ESP_ERR_INVALID_STATE).unwrap());
+    }
+
+    ISR_ALLOC_FLAGS.store(interrupt.flags(), Ordering::SeqCst);
+
+    Ok(())
+}
+
+#[cfg(all(not(feature = "riscv-ulp-hal"), feature = "alloc"))]
+pub(crate) fn enable_isr_service() -> Result<(), EspError> {
     use core::sync::atomic::Ordering;
 
     if !ISR_SERVICE_ENABLED.load(Ordering::SeqCst) {
         let _ = ISR_SERVICE_ENABLED_CS.enter();
 
         if !ISR_SERVICE_ENABLED.load(Ordering::SeqCst) {
-            esp!(unsafe { gpio_install_isr_service(0) })?;
+            esp!(unsafe { gpio_install_isr_service(ISR_ALLOC_FLAGS.load(Ordering::SeqCst) as _) })?;
 
             ISR_SERVICE_ENABLED.store(true, Ordering::SeqCst);
         }
@@ -1374,6 +1876,509 @@ macro_rules! pin {
     };
 }
 
+/// Routing of arbitrary peripheral signals through the GPIO matrix.
+///
+/// Besides connecting a pin to a peripheral signal the drivers in this crate do not route themselves,
+/// the GPIO matrix allows inverting signals, and feeding the output signal of one peripheral into
+/// the input signal of another one through a single pin (see `loopback`), without any external wiring.
+#[cfg(not(feature = "riscv-ulp-hal"))]
+pub mod matrix {
+    use core::marker::PhantomData;
+
+    use esp_idf_sys::*;
+
+    use crate::peripheral::Peripheral;
+
+    use super::{IOPin, InputPin, Level, OutputPin, Pin};
+
+    #[cfg(esp32)]
+    const CONST_ZERO_INPUT: u32 = 0x30;
+    #[cfg(esp32)]
+    const CONST_ONE_INPUT: u32 = 0x38;
+
+    #[cfg(any(esp32s2, esp32s3))]
+    const CONST_ZERO_INPUT: u32 = 0x3c;
+    #[cfg(any(esp32s2, esp32s3))]
+    const CONST_ONE_INPUT: u32 = 0x38;
+
+    #[cfg(esp32c3)]
+    const CONST_ZERO_INPUT: u32 = 0x1f;
+    #[cfg(esp32c3)]
+    const CONST_ONE_INPUT: u32 = 0x1e;
+
+    /// Peripheral input signals which can be routed from a GPIO
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    #[repr(u32)]
+    pub enum InputSignal {
+        Uart0Rx = U0RXD_IN_IDX as _,
+        Uart1Rx = U1RXD_IN_IDX as _,
+        #[cfg(any(esp32, esp32s3))]
+        Uart2Rx = U2RXD_IN_IDX as _,
+        I2c0Scl = I2CEXT0_SCL_IN_IDX as _,
+        I2c0Sda = I2CEXT0_SDA_IN_IDX as _,
+        #[cfg(not(esp32c3))]
+        I2c1Scl = I2CEXT1_SCL_IN_IDX as _,
+        #[cfg(not(esp32c3))]
+        I2c1Sda = I2CEXT1_SDA_IN_IDX as _,
+        TwaiRx = TWAI_RX_IDX as _,
+        RmtIn0 = RMT_SIG_IN0_IDX as _,
+        RmtIn1 = RMT_SIG_IN1_IDX as _,
+        #[cfg(not(esp32c3))]
+        RmtIn2 = RMT_SIG_IN2_IDX as _,
+        #[cfg(not(esp32c3))]
+        RmtIn3 = RMT_SIG_IN3_IDX as _,
+        #[cfg(esp32)]
+        RmtIn4 = RMT_SIG_IN4_IDX as _,
+        #[cfg(esp32)]
+        RmtIn5 = RMT_SIG_IN5_IDX as _,
+        #[cfg(esp32)]
+        RmtIn6 = RMT_SIG_IN6_IDX as _,
+        #[cfg(esp32)]
+        RmtIn7 = RMT_SIG_IN7_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit0Ch0Signal = PCNT_SIG_CH0_IN0_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit0Ch0Control = PCNT_CTRL_CH0_IN0_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit0Ch1Signal = PCNT_SIG_CH1_IN0_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit0Ch1Control = PCNT_CTRL_CH1_IN0_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit1Ch0Signal = PCNT_SIG_CH0_IN1_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit1Ch0Control = PCNT_CTRL_CH0_IN1_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit1Ch1Signal = PCNT_SIG_CH1_IN1_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit1Ch1Control = PCNT_CTRL_CH1_IN1_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit2Ch0Signal = PCNT_SIG_CH0_IN2_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit2Ch0Control = PCNT_CTRL_CH0_IN2_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit2Ch1Signal = PCNT_SIG_CH1_IN2_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit2Ch1Control = PCNT_CTRL_CH1_IN2_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit3Ch0Signal = PCNT_SIG_CH0_IN3_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit3Ch0Control = PCNT_CTRL_CH0_IN3_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit3Ch1Signal = PCNT_SIG_CH1_IN3_IDX as _,
+        #[cfg(not(esp32c3))]
+        PcntUnit3Ch1Control = PCNT_CTRL_CH1_IN3_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit4Ch0Signal = PCNT_SIG_CH0_IN4_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit4Ch0Control = PCNT_CTRL_CH0_IN4_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit4Ch1Signal = PCNT_SIG_CH1_IN4_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit4Ch1Control = PCNT_CTRL_CH1_IN4_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit5Ch0Signal = PCNT_SIG_CH0_IN5_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit5Ch0Control = PCNT_CTRL_CH0_IN5_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit5Ch1Signal = PCNT_SIG_CH1_IN5_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit5Ch1Control = PCNT_CTRL_CH1_IN5_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit6Ch0Signal = PCNT_SIG_CH0_IN6_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit6Ch0Control = PCNT_CTRL_CH0_IN6_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit6Ch1Signal = PCNT_SIG_CH1_IN6_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit6Ch1Control = PCNT_CTRL_CH1_IN6_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit7Ch0Signal = PCNT_SIG_CH0_IN7_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit7Ch0Control = PCNT_CTRL_CH0_IN7_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit7Ch1Signal = PCNT_SIG_CH1_IN7_IDX as _,
+        #[cfg(esp32)]
+        PcntUnit7Ch1Control = PCNT_CTRL_CH1_IN7_IDX as _,
+        #[cfg(any(esp32s2, esp32s3))]
+        UsbOtgIdDig = USB_OTG_IDDIG_IN_IDX as _,
+        #[cfg(any(esp32s2, esp32s3))]
+        UsbOtgAValid = USB_OTG_AVALID_IN_IDX as _,
+        #[cfg(any(esp32s2, esp32s3))]
+        UsbSrpBValid = USB_SRP_BVALID_IN_IDX as _,
+        #[cfg(any(esp32s2, esp32s3))]
+        UsbOtgVBusValid = USB_OTG_VBUSVALID_IN_IDX as _,
+    }
+
+    impl From<InputSignal> for u32 {
+        fn from(signal: InputSignal) -> u32 {
+            signal as u32
+        }
+    }
+
+    /// Peripheral output signals which can be routed to a GPIO
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    #[repr(u32)]
+    pub enum OutputSignal {
+        Uart0Tx = U0TXD_OUT_IDX as _,
+        Uart1Tx = U1TXD_OUT_IDX as _,
+        #[cfg(any(esp32, esp32s3))]
+        Uart2Tx = U2TXD_OUT_IDX as _,
+        I2c0Scl = I2CEXT0_SCL_OUT_IDX as _,
+        I2c0Sda = I2CEXT0_SDA_OUT_IDX as _,
+        #[cfg(not(esp32c3))]
+        I2c1Scl = I2CEXT1_SCL_OUT_IDX as _,
+        #[cfg(not(esp32c3))]
+        I2c1Sda = I2CEXT1_SDA_OUT_IDX as _,
+        TwaiTx = TWAI_TX_IDX as _,
+        RmtOut0 = RMT_SIG_OUT0_IDX as _,
+        RmtOut1 = RMT_SIG_OUT1_IDX as _,
+        #[cfg(not(esp32c3))]
+        RmtOut2 = RMT_SIG_OUT2_IDX as _,
+        #[cfg(not(esp32c3))]
+        RmtOut3 = RMT_SIG_OUT3_IDX as _,
+        #[cfg(esp32)]
+        RmtOut4 = RMT_SIG_OUT4_IDX as _,
+        #[cfg(esp32)]
+        RmtOut5 = RMT_SIG_OUT5_IDX as _,
+        #[cfg(esp32)]
+        RmtOut6 = RMT_SIG_OUT6_IDX as _,
+        #[cfg(esp32)]
+        RmtOut7 = RMT_SIG_OUT7_IDX as _,
+        #[cfg(esp32)]
+        LedcHighSpeed0 = LEDC_HS_SIG_OUT0_IDX as _,
+        #[cfg(esp32)]
+        LedcHighSpeed1 = LEDC_HS_SIG_OUT1_IDX as _,
+        #[cfg(esp32)]
+        LedcHighSpeed2 = LEDC_HS_SIG_OUT2_IDX as _,
+        #[cfg(esp32)]
+        LedcHighSpeed3 = LEDC_HS_SIG_OUT3_IDX as _,
+        #[cfg(esp32)]
+        LedcHighSpeed4 = LEDC_HS_SIG_OUT4_IDX as _,
+        #[cfg(esp32)]
+        LedcHighSpeed5 = LEDC_HS_SIG_OUT5_IDX as _,
+        #[cfg(esp32)]
+        LedcHighSpeed6 = LEDC_HS_SIG_OUT6_IDX as _,
+        #[cfg(esp32)]
+        LedcHighSpeed7 = LEDC_HS_SIG_OUT7_IDX as _,
+        LedcLowSpeed0 = LEDC_LS_SIG_OUT0_IDX as _,
+        LedcLowSpeed1 = LEDC_LS_SIG_OUT1_IDX as _,
+        LedcLowSpeed2 = LEDC_LS_SIG_OUT2_IDX as _,
+        LedcLowSpeed3 = LEDC_LS_SIG_OUT3_IDX as _,
+        LedcLowSpeed4 = LEDC_LS_SIG_OUT4_IDX as _,
+        LedcLowSpeed5 = LEDC_LS_SIG_OUT5_IDX as _,
+        #[cfg(not(esp32c3))]
+        LedcLowSpeed6 = LEDC_LS_SIG_OUT6_IDX as _,
+        #[cfg(not(esp32c3))]
+        LedcLowSpeed7 = LEDC_LS_SIG_OUT7_IDX as _,
+        Gpio = SIG_GPIO_OUT_IDX as _,
+    }
+
+    impl From<OutputSignal> for u32 {
+        fn from(signal: OutputSignal) -> u32 {
+            signal as u32
+        }
+    }
+
+    /// A pin connected to peripheral signals through the GPIO matrix.
+    ///
+    /// When dropped, the input signals get disconnected (i.e. tied low) and the pin is reset.
+    pub struct MatrixPin<'d> {
+        pin: i32,
+        input: Option<InputSignal>,
+        _p: PhantomData<&'d mut ()>,
+    }
+
+    impl<'d> MatrixPin<'d> {
+        pub fn pin(&self) -> i32 {
+            self.pin
+        }
+    }
+
+    impl<'d> Drop for MatrixPin<'d> {
+        fn drop(&mut self) {
+            if let Some(input) = self.input {
+                connect_constant(input, Level::Low);
+            }
+
+            unsafe { super::reset_pin(self.pin, gpio_mode_t_GPIO_MODE_DISABLE) }.unwrap();
+        }
+    }
+
+    /// Routes the level of `pin` to the peripheral input `signal`, optionally inverted
+    pub fn connect_input<'d>(
+        pin: impl Peripheral<P = impl InputPin> + 'd,
+        signal: InputSignal,
+        invert: bool,
+    ) -> Result<MatrixPin<'d>, EspError> {
+        crate::into_ref!(pin);
+
+        let pin = pin.pin();
+
+        unsafe {
+            super::reset_pin(pin, gpio_mode_t_GPIO_MODE_INPUT)?;
+            esp_rom_gpio_connect_in_signal(pin as _, signal.into(), invert);
+        }
+
+        Ok(MatrixPin {
+            pin,
+            input: Some(signal),
+            _p: PhantomData,
+        })
+    }
+
+    /// Drives `pin` with the peripheral output `signal`, optionally inverted
+    pub fn connect_output<'d>(
+        pin: impl Peripheral<P = impl OutputPin> + 'd,
+        signal: OutputSignal,
+        invert: bool,
+    ) -> Result<MatrixPin<'d>, EspError> {
+        crate::into_ref!(pin);
+
+        let pin = pin.pin();
+
+        unsafe {
+            super::reset_pin(pin, gpio_mode_t_GPIO_MODE_OUTPUT)?;
+            esp_rom_gpio_connect_out_signal(pin as _, signal.into(), invert, false);
+        }
+
+        Ok(MatrixPin {
+            pin,
+            input: None,
+            _p: PhantomData,
+        })
+    }
+
+    /// Drives `pin` with the peripheral `output` signal, and routes the level of the pin
+    /// back into the peripheral `input` signal, e.g. to count the pulses of an RMT channel
+    /// with a PCNT unit. The pin can still be observed externally.
+    pub fn loopback<'d>(
+        pin: impl Peripheral<P = impl IOPin> + 'd,
+        output: OutputSignal,
+        input: InputSignal,
+    ) -> Result<MatrixPin<'d>, EspError> {
+        crate::into_ref!(pin);
+
+        let pin = pin.pin();
+
+        unsafe {
+            super::reset_pin(pin, gpio_mode_t_GPIO_MODE_INPUT_OUTPUT)?;
+            esp_rom_gpio_connect_out_signal(pin as _, output.into(), false, false);
+            esp_rom_gpio_connect_in_signal(pin as _, input.into(), false);
+        }
+
+        Ok(MatrixPin {
+            pin,
+            input: Some(input),
+            _p: PhantomData,
+        })
+    }
+
+    /// Ties the peripheral input `signal` to a constant level, disconnecting it from any pin
+    pub fn connect_constant(signal: InputSignal, level: Level) {
+        let source = match level {
+            Level::Low => CONST_ZERO_INPUT,
+            Level::High => CONST_ONE_INPUT,
+        };
+
+        unsafe {
+            esp_rom_gpio_connect_in_signal(source, signal.into(), false);
+        }
+    }
+}
+
+/// Dedicated GPIO bundles.
+///
+/// A bundle routes up to 8 pins to the dedicated GPIO channels of the CPU, so that the levels
+/// of all of them are written or read at once with a single CPU instruction, instead of going
+/// through the GPIO matrix registers one pin at a time.
+///
+/// Bit `n` of the values written to and read from a bundle corresponds to the `n`-th pin
+/// the bundle was created with.
+#[cfg(all(not(feature = "riscv-ulp-hal"), any(esp32s2, esp32s3, esp32c3)))]
+pub mod dedicated {
+    use core::marker::PhantomData;
+
+    use esp_idf_sys::*;
+
+    use crate::peripheral::PeripheralRef;
+
+    use super::{
+        AnyIOPin, AnyInputPin, AnyOutputPin, Input, InputMode, InputOutput, Output, OutputMode,
+        ParallelBus, ParallelBusRead, Pin,
+    };
+
+    /// Number of the dedicated GPIO channels of each CPU
+    pub const MAX_PINS: usize = 8;
+
+    /// A bundle of `N` pins operated by the dedicated GPIO channels of the CPU it was created on.
+    ///
+    /// All operations are lock-free and can be called from an ISR. The bundle can only be
+    /// operated from the CPU core it was created on, hence it is not `Send`.
+    pub struct DedicatedGpioBundle<'d, MODE, const N: usize> {
+        handle: dedic_gpio_bundle_handle_t,
+        pins: [i32; N],
+        _p: PhantomData<&'d mut ()>,
+        _mode: PhantomData<MODE>,
+    }
+
+    impl<'d, const N: usize> DedicatedGpioBundle<'d, Input, N> {
+        pub fn input(pins: [PeripheralRef<'d, AnyInputPin>; N]) -> Result<Self, EspError> {
+            Self::new(pins.map(|pin| pin.pin()), true, false)
+        }
+    }
+
+    impl<'d, const N: usize> DedicatedGpioBundle<'d, Output, N> {
+        pub fn output(pins: [PeripheralRef<'d, AnyOutputPin>; N]) -> Result<Self, EspError> {
+            Self::new(pins.map(|pin| pin.pin()), false, true)
+        }
+    }
+
+    impl<'d, const N: usize> DedicatedGpioBundle<'d, InputOutput, N> {
+        pub fn input_output(pins: [PeripheralRef<'d, AnyIOPin>; N]) -> Result<Self, EspError> {
+            Self::new(pins.map(|pin| pin.pin()), true, true)
+        }
+    }
+
+    impl<'d, MODE, const N: usize> DedicatedGpioBundle<'d, MODE, N> {
+        /// Mask covering all pins of the bundle
+        pub const MASK: u32 = ((1_u64 << N) - 1) as u32;
+
+        fn new(pins: [i32; N], input: bool, output: bool) -> Result<Self, EspError> {
+            if N == 0 || N > MAX_PINS {
+                return Err(EspError::from(ESP_ERR_INVALID_ARG).unwrap());
+            }
+
+            let mut config = dedic_gpio_bundle_config_t {
+                gpio_array: pins.as_ptr() as *const _ as *mut _,
+                array_size: N as _,
+                ..Default::default()
+            };
+
+            config.flags.set_in_en(input as _);
+            config.flags.set_out_en(output as _);
+
+            let mut handle: dedic_gpio_bundle_handle_t = core::ptr::null_mut();
+
+            esp!(unsafe { dedic_gpio_new_bundle(&config, &mut handle) })?;
+
+            Ok(Self {
+                handle,
+                pins,
+                _p: PhantomData,
+                _mode: PhantomData,
+            })
+        }
+
+        /// Returns the GPIO numbers of the pins in the bundle
+        pub fn pins(&self) -> &[i32; N] {
+            &self.pins
+        }
+
+        /// Returns the levels of the bundle pins as a bit mask
+        #[inline(always)]
+        #[link_section = ".iram1.dedic_read"]
+        pub fn read(&self) -> u32
+        where
+            MODE: InputMode,
+        {
+            unsafe { dedic_gpio_bundle_read_in(self.handle) }
+        }
+
+        /// Returns the levels the bundle pins are driven to as a bit mask
+        #[inline(always)]
+        #[link_section = ".iram1.dedic_read_output"]
+        pub fn read_output(&self) -> u32
+        where
+            MODE: OutputMode,
+        {
+            unsafe { dedic_gpio_bundle_read_out(self.handle) }
+        }
+
+        /// Drives all pins of the bundle to the levels in `value`
+        #[inline(always)]
+        #[link_section = ".iram1.dedic_write"]
+        pub fn write(&mut self, value: u32)
+        where
+            MODE: OutputMode,
+        {
+            self.write_masked(Self::MASK, value)
+        }
+
+        /// Drives the pins of the bundle selected by `mask` to the levels in `value`,
+        /// leaving the other pins unchanged
+        #[inline(always)]
+        #[link_section = ".iram1.dedic_write_masked"]
+        pub fn write_masked(&mut self, mask: u32, value: u32)
+        where
+            MODE: OutputMode,
+        {
+            unsafe { dedic_gpio_bundle_write(self.handle, mask & Self::MASK, value) }
+        }
+
+        #[inline(always)]
+        pub fn set_high(&mut self, mask: u32)
+        where
+            MODE: OutputMode,
+        {
+            self.write_masked(mask, u32::MAX)
+        }
+
+        #[inline(always)]
+        pub fn set_low(&mut self, mask: u32)
+        where
+            MODE: OutputMode,
+        {
+            self.write_masked(mask, 0)
+        }
+
+        #[inline(always)]
+        pub fn toggle(&mut self, mask: u32)
+        where
+            MODE: OutputMode,
+        {
+            let value = !self.read_output();
+
+            self.write_masked(mask, value)
+        }
+    }
+
+    impl<'d, MODE, const N: usize> ParallelBus for DedicatedGpioBundle<'d, MODE, N>
+    where
+        MODE: OutputMode,
+    {
+        type Error = core::convert::Infallible;
+
+        fn width(&self) -> usize {
+            N
+        }
+
+        fn write(&mut self, value: u32) -> Result<(), Self::Error> {
+            DedicatedGpioBundle::write(self, value);
+
+            Ok(())
+        }
+    }
+
+    impl<'d, MODE, const N: usize> ParallelBusRead for DedicatedGpioBundle<'d, MODE, N>
+    where
+        MODE: InputMode + OutputMode,
+    {
+        fn read(&mut self) -> Result<u32, Self::Error> {
+            Ok(DedicatedGpioBundle::read(self))
+        }
+    }
+
+    impl<'d, MODE, const N: usize> Drop for DedicatedGpioBundle<'d, MODE, N> {
+        fn drop(&mut self) {
+            esp!(unsafe { dedic_gpio_del_bundle(self.handle) }).unwrap();
+
+            for pin in self.pins {
+                unsafe { super::reset_pin(pin, gpio_mode_t_GPIO_MODE_DISABLE) }.unwrap();
+            }
+        }
+    }
+}
+
 #[cfg(esp32)]
 mod chip {
     #[cfg(not(feature = "riscv-ulp-hal"))]
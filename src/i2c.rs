@@ -1,4 +1,6 @@
 use core::marker::PhantomData;
+#[cfg(feature = "alloc")]
+use core::time::Duration;
 
 use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
 
@@ -9,8 +11,14 @@ use crate::gpio::*;
 use crate::peripheral::{Peripheral, PeripheralRef};
 use crate::units::*;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub use embedded_hal::i2c::Operation;
 
+/// Size of the I2C hardware TX FIFO, which is the same on all chips
+const TX_FIFO_LEN: usize = 32;
+
 crate::embedded_hal_error!(
     I2cError,
     embedded_hal::i2c::Error,
@@ -22,14 +30,44 @@ pub type I2cSlaveConfig = config::SlaveConfig;
 
 /// I2C configuration
 pub mod config {
+    use core::time::Duration;
+
+    use esp_idf_sys::TickType_t;
+
+    use crate::interrupt::InterruptConfig;
     use crate::units::*;
 
+    /// Setup (or sample) and hold times of a bus condition, in APB clock cycles
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub struct BusTiming {
+        pub setup: u32,
+        pub hold: u32,
+    }
+
+    impl BusTiming {
+        pub fn new(setup: u32, hold: u32) -> Self {
+            Self { setup, hold }
+        }
+    }
+
     /// I2C Master configuration
     #[derive(Copy, Clone)]
     pub struct MasterConfig {
         pub baudrate: Hertz,
         pub sda_pullup_enabled: bool,
         pub scl_pullup_enabled: bool,
+        /// Maximum time a slave is allowed to stretch the clock, or `None` for the ESP-IDF default.
+        ///
+        /// On esp32 the value is in APB clock cycles; on the newer chips the timeout is
+        /// 2 to the power of this value, in APB clock cycles.
+        pub timeout: Option<u32>,
+        /// SDA sample time after the SCL rising edge (`setup`) and SDA hold time after the SCL falling edge (`hold`)
+        pub data_timing: Option<BusTiming>,
+        /// SCL setup and hold times of the START condition
+        pub start_timing: Option<BusTiming>,
+        /// SCL setup and hold times of the STOP condition
+        pub stop_timing: Option<BusTiming>,
+        pub interrupt: InterruptConfig,
     }
 
     impl MasterConfig {
@@ -54,6 +92,36 @@ pub mod config {
             self.scl_pullup_enabled = enable;
             self
         }
+
+        #[must_use]
+        pub fn timeout(mut self, timeout: Option<u32>) -> Self {
+            self.timeout = timeout;
+            self
+        }
+
+        #[must_use]
+        pub fn data_timing(mut self, data_timing: Option<BusTiming>) -> Self {
+            self.data_timing = data_timing;
+            self
+        }
+
+        #[must_use]
+        pub fn start_timing(mut self, start_timing: Option<BusTiming>) -> Self {
+            self.start_timing = start_timing;
+            self
+        }
+
+        #[must_use]
+        pub fn stop_timing(mut self, stop_timing: Option<BusTiming>) -> Self {
+            self.stop_timing = stop_timing;
+            self
+        }
+
+        #[must_use]
+        pub fn interrupt(mut self, interrupt: InterruptConfig) -> Self {
+            self.interrupt = interrupt;
+            self
+        }
     }
 
     impl Default for MasterConfig {
@@ -62,6 +130,11 @@ pub mod config {
                 baudrate: Hertz(1_000_000),
                 sda_pullup_enabled: true,
                 scl_pullup_enabled: true,
+                timeout: None,
+                data_timing: None,
+                start_timing: None,
+                stop_timing: None,
+                interrupt: Default::default(),
             }
         }
     }
@@ -73,6 +146,7 @@ pub mod config {
         pub scl_pullup_enabled: bool,
         pub rx_buf_len: usize,
         pub tx_buf_len: usize,
+        pub interrupt: InterruptConfig,
     }
 
     impl SlaveConfig {
@@ -103,6 +177,12 @@ pub mod config {
             self.tx_buf_len = len;
             self
         }
+
+        #[must_use]
+        pub fn interrupt(mut self, interrupt: InterruptConfig) -> Self {
+            self.interrupt = interrupt;
+            self
+        }
     }
 
     impl Default for SlaveConfig {
@@ -112,6 +192,71 @@ pub mod config {
                 scl_pullup_enabled: true,
                 rx_buf_len: 0,
                 tx_buf_len: 0,
+                interrupt: Default::default(),
+            }
+        }
+    }
+
+    /// Width of the register pointer the master writes at the start of each transaction
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum RegisterAddressWidth {
+        /// 8-bit register pointer, as used by most sensors
+        Bits8,
+        /// 16-bit (big endian) register pointer, as used by larger EEPROMs
+        Bits16,
+    }
+
+    /// I2C register-map slave configuration
+    #[derive(Copy, Clone)]
+    pub struct RegisterSlaveConfig {
+        pub address_width: RegisterAddressWidth,
+        /// Bytes received within this many ticks of each other are considered to belong
+        /// to the same master write transaction
+        pub transaction_gap: TickType_t,
+        /// Maximum number of registers staged for reading after the register pointer is set;
+        /// capped at the size of the hardware TX FIFO (32 bytes)
+        pub read_ahead: usize,
+        /// Polling interval used by the async API
+        pub poll_interval: Duration,
+    }
+
+    impl RegisterSlaveConfig {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        #[must_use]
+        pub fn address_width(mut self, address_width: RegisterAddressWidth) -> Self {
+            self.address_width = address_width;
+            self
+        }
+
+        #[must_use]
+        pub fn transaction_gap(mut self, transaction_gap: TickType_t) -> Self {
+            self.transaction_gap = transaction_gap;
+            self
+        }
+
+        #[must_use]
+        pub fn read_ahead(mut self, read_ahead: usize) -> Self {
+            self.read_ahead = read_ahead;
+            self
+        }
+
+        #[must_use]
+        pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+            self.poll_interval = poll_interval;
+            self
+        }
+    }
+
+    impl Default for RegisterSlaveConfig {
+        fn default() -> Self {
+            Self {
+                address_width: RegisterAddressWidth::Bits8,
+                transaction_gap: 1,
+                read_ahead: 32,
+                poll_interval: Duration::from_millis(10),
             }
         }
     }
@@ -126,6 +271,10 @@ where
     I2C: I2c,
 {
     _i2c: PeripheralRef<'d, I2C>,
+    sda: i32,
+    scl: i32,
+    sda_pullup_enabled: bool,
+    scl_pullup_enabled: bool,
 }
 
 impl<'d, I2C> I2cMasterDriver<'d, I2C>
@@ -167,11 +316,150 @@ where
                 i2c_mode_t_I2C_MODE_MASTER,
                 0, // Not used in master mode
                 0, // Not used in master mode
-                0,
-            ) // TODO: set flags
+                config.interrupt.into(),
+            )
         })?;
 
-        Ok(I2cMasterDriver { _i2c: i2c })
+        if let Some(timeout) = config.timeout {
+            esp!(unsafe { i2c_set_timeout(I2C::port(), timeout as _) })?;
+        }
+
+        if let Some(timing) = config.data_timing {
+            esp!(unsafe { i2c_set_data_timing(I2C::port(), timing.setup as _, timing.hold as _) })?;
+        }
+
+        if let Some(timing) = config.start_timing {
+            esp!(unsafe {
+                i2c_set_start_timing(I2C::port(), timing.setup as _, timing.hold as _)
+            })?;
+        }
+
+        if let Some(timing) = config.stop_timing {
+            esp!(unsafe { i2c_set_stop_timing(I2C::port(), timing.setup as _, timing.hold as _) })?;
+        }
+
+        Ok(I2cMasterDriver {
+            _i2c: i2c,
+            sda: sys_config.sda_io_num,
+            scl: sys_config.scl_io_num,
+            sda_pullup_enabled: config.sda_pullup_enabled,
+            scl_pullup_enabled: config.scl_pullup_enabled,
+        })
+    }
+
+    /// Probes all non-reserved 7-bit addresses (0x08 - 0x77) and returns those acknowledged by a slave
+    pub fn scan(&mut self, timeout: TickType_t) -> Result<heapless::Vec<u8, 112>, EspError> {
+        let mut addresses = heapless::Vec::new();
+
+        for addr in 0x08..0x78 {
+            match self.write(addr, &[], timeout) {
+                Ok(()) => addresses.push(addr).unwrap(),
+                Err(err) if err.code() == ESP_FAIL => (),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(addresses)
+    }
+
+    /// Recovers the bus from a slave stuck in the middle of a transfer and holding SDA low.
+    ///
+    /// The SDA and SCL pins are temporarily taken over as GPIOs, and SCL is clocked
+    /// (at most 9 times) until the slave releases SDA. A STOP condition is then issued
+    /// and the pins are routed back to the I2C peripheral - also if any of the steps before fails.
+    ///
+    /// Returns `ESP_ERR_INVALID_STATE` if SDA is still held low afterwards.
+    pub fn recover(&mut self) -> Result<(), EspError> {
+        let released = unsafe { self.clock_out_stuck_slave() };
+
+        unsafe {
+            esp!(i2c_set_pin(
+                I2C::port(),
+                self.sda,
+                self.scl,
+                self.sda_pullup_enabled,
+                self.scl_pullup_enabled,
+                i2c_mode_t_I2C_MODE_MASTER,
+            ))?;
+
+            esp!(i2c_reset_tx_fifo(I2C::port()))?;
+            esp!(i2c_reset_rx_fifo(I2C::port()))?;
+        }
+
+        if released? {
+            Ok(())
+        } else {
+            Err(EspError::from(ESP_ERR_INVALID_STATE).unwrap())
+        }
+    }
+
+    /// Bit-bangs the SCL clock pulses and the STOP condition of `recover`, leaving the pins in GPIO mode.
+    ///
+    /// Returns whether SDA was released.
+    unsafe fn clock_out_stuck_slave(&self) -> Result<bool, EspError> {
+        const HALF_PERIOD_US: u32 = 5;
+
+        for (pin, pullup) in [
+            (self.sda, self.sda_pullup_enabled),
+            (self.scl, self.scl_pullup_enabled),
+        ] {
+            esp!(gpio_set_level(pin, 1))?;
+            esp!(gpio_set_direction(
+                pin,
+                gpio_mode_t_GPIO_MODE_INPUT_OUTPUT_OD
+            ))?;
+
+            if pullup {
+                esp!(gpio_set_pull_mode(pin, gpio_pull_mode_t_GPIO_PULLUP_ONLY))?;
+            }
+        }
+
+        Ets::delay_us(HALF_PERIOD_US);
+
+        for _ in 0..9 {
+            if gpio_get_level(self.sda) != 0 {
+                break;
+            }
+
+            esp!(gpio_set_level(self.scl, 0))?;
+            Ets::delay_us(HALF_PERIOD_US);
+            esp!(gpio_set_level(self.scl, 1))?;
+            Ets::delay_us(HALF_PERIOD_US);
+        }
+
+        // STOP condition: SDA rising while SCL is high
+        esp!(gpio_set_level(self.scl, 0))?;
+        Ets::delay_us(HALF_PERIOD_US);
+        esp!(gpio_set_level(self.sda, 0))?;
+        Ets::delay_us(HALF_PERIOD_US);
+        esp!(gpio_set_level(self.scl, 1))?;
+        Ets::delay_us(HALF_PERIOD_US);
+        esp!(gpio_set_level(self.sda, 1))?;
+        Ets::delay_us(HALF_PERIOD_US);
+
+        Ok(gpio_get_level(self.sda) != 0)
+    }
+
+    /// Classifies an error returned by one of the native methods of this driver.
+    ///
+    /// The ESP-IDF I2C driver reports lost arbitration and bus errors as timeouts, so a timeout
+    /// is reported as `ErrorKind::ArbitrationLoss` when the controller status register still
+    /// flags the lost arbitration, as `ErrorKind::Bus` when a slave keeps holding SDA low
+    /// (see `recover`), and as a timeout (`I2cError::is_timeout`) otherwise.
+    pub fn classify(&self, err: EspError) -> I2cError {
+        if err.code() == ESP_ERR_TIMEOUT {
+            let (sda, scl) = unsafe { (gpio_get_level(self.sda), gpio_get_level(self.scl)) };
+
+            if regs::arbitration_lost(I2C::port()) {
+                I2cError::new(ErrorKind::ArbitrationLoss, err)
+            } else if sda == 0 && scl != 0 {
+                I2cError::new(ErrorKind::Bus, err)
+            } else {
+                I2cError::other(err)
+            }
+        } else {
+            to_i2c_err(err)
+        }
     }
 
     pub fn read(
@@ -179,11 +467,83 @@ where
         addr: u8,
         buffer: &mut [u8],
         timeout: TickType_t,
+    ) -> Result<(), EspError> {
+        self.read_from(Address::SevenBit(addr), buffer, timeout)
+    }
+
+    pub fn write(&mut self, addr: u8, bytes: &[u8], timeout: TickType_t) -> Result<(), EspError> {
+        self.write_to(Address::SevenBit(addr), bytes, timeout)
+    }
+
+    pub fn write_read(
+        &mut self,
+        addr: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+        timeout: TickType_t,
+    ) -> Result<(), EspError> {
+        self.write_read_to(Address::SevenBit(addr), bytes, buffer, timeout)
+    }
+
+    pub fn transaction<'a>(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'a>],
+        timeout: TickType_t,
+    ) -> Result<(), EspError> {
+        self.transaction_with(Address::SevenBit(address), operations, timeout)
+    }
+
+    /// Same as `read`, but addressing a slave with a 10-bit address
+    pub fn read_ten_bit(
+        &mut self,
+        addr: u16,
+        buffer: &mut [u8],
+        timeout: TickType_t,
+    ) -> Result<(), EspError> {
+        self.read_from(Address::TenBit(addr), buffer, timeout)
+    }
+
+    /// Same as `write`, but addressing a slave with a 10-bit address
+    pub fn write_ten_bit(
+        &mut self,
+        addr: u16,
+        bytes: &[u8],
+        timeout: TickType_t,
+    ) -> Result<(), EspError> {
+        self.write_to(Address::TenBit(addr), bytes, timeout)
+    }
+
+    /// Same as `write_read`, but addressing a slave with a 10-bit address
+    pub fn write_read_ten_bit(
+        &mut self,
+        addr: u16,
+        bytes: &[u8],
+        buffer: &mut [u8],
+        timeout: TickType_t,
+    ) -> Result<(), EspError> {
+        self.write_read_to(Address::TenBit(addr), bytes, buffer, timeout)
+    }
+
+    /// Same as `transaction`, but addressing a slave with a 10-bit address
+    pub fn transaction_ten_bit<'a>(
+        &mut self,
+        address: u16,
+        operations: &mut [Operation<'a>],
+        timeout: TickType_t,
+    ) -> Result<(), EspError> {
+        self.transaction_with(Address::TenBit(address), operations, timeout)
+    }
+
+    fn read_from(
+        &mut self,
+        addr: Address,
+        buffer: &mut [u8],
+        timeout: TickType_t,
     ) -> Result<(), EspError> {
         let mut command_link = CommandLink::new()?;
 
-        command_link.master_start()?;
-        command_link.master_write_byte((addr << 1) | (i2c_rw_t_I2C_MASTER_READ as u8), true)?;
+        command_link.master_address(addr, true, false)?;
 
         if !buffer.is_empty() {
             command_link.master_read(buffer, AckType::LastNack)?;
@@ -194,11 +554,15 @@ where
         self.cmd_begin(&command_link, timeout)
     }
 
-    pub fn write(&mut self, addr: u8, bytes: &[u8], timeout: TickType_t) -> Result<(), EspError> {
+    fn write_to(
+        &mut self,
+        addr: Address,
+        bytes: &[u8],
+        timeout: TickType_t,
+    ) -> Result<(), EspError> {
         let mut command_link = CommandLink::new()?;
 
-        command_link.master_start()?;
-        command_link.master_write_byte((addr << 1) | (i2c_rw_t_I2C_MASTER_WRITE as u8), true)?;
+        command_link.master_address(addr, false, false)?;
 
         if !bytes.is_empty() {
             command_link.master_write(bytes, true)?;
@@ -209,24 +573,22 @@ where
         self.cmd_begin(&command_link, timeout)
     }
 
-    pub fn write_read(
+    fn write_read_to(
         &mut self,
-        addr: u8,
+        addr: Address,
         bytes: &[u8],
         buffer: &mut [u8],
         timeout: TickType_t,
     ) -> Result<(), EspError> {
         let mut command_link = CommandLink::new()?;
 
-        command_link.master_start()?;
-        command_link.master_write_byte((addr << 1) | (i2c_rw_t_I2C_MASTER_WRITE as u8), true)?;
+        command_link.master_address(addr, false, false)?;
 
         if !bytes.is_empty() {
             command_link.master_write(bytes, true)?;
         }
 
-        command_link.master_start()?;
-        command_link.master_write_byte((addr << 1) | (i2c_rw_t_I2C_MASTER_READ as u8), true)?;
+        command_link.master_address(addr, true, true)?;
 
         if !buffer.is_empty() {
             command_link.master_read(buffer, AckType::LastNack)?;
@@ -237,31 +599,97 @@ where
         self.cmd_begin(&command_link, timeout)
     }
 
-    pub fn transaction<'a>(
+    fn write_iter_to<B>(
         &mut self,
-        address: u8,
+        addr: Address,
+        bytes: B,
+        timeout: TickType_t,
+    ) -> Result<(), EspError>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let mut command_link = CommandLink::new()?;
+
+        command_link.master_address(addr, false, false)?;
+
+        for byte in bytes {
+            command_link.master_write_byte(byte, true)?;
+        }
+
+        command_link.master_stop()?;
+
+        self.cmd_begin(&command_link, timeout)
+    }
+
+    fn write_iter_read_to<B>(
+        &mut self,
+        addr: Address,
+        bytes: B,
+        buffer: &mut [u8],
+        timeout: TickType_t,
+    ) -> Result<(), EspError>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let mut command_link = CommandLink::new()?;
+
+        command_link.master_address(addr, false, false)?;
+
+        for byte in bytes {
+            command_link.master_write_byte(byte, true)?;
+        }
+
+        command_link.master_address(addr, true, true)?;
+
+        if !buffer.is_empty() {
+            command_link.master_read(buffer, AckType::LastNack)?;
+        }
+
+        command_link.master_stop()?;
+
+        self.cmd_begin(&command_link, timeout)
+    }
+
+    fn transaction_with<'a>(
+        &mut self,
+        address: Address,
         operations: &mut [Operation<'a>],
         timeout: TickType_t,
     ) -> Result<(), EspError> {
+        self.transaction_iter_with(
+            address,
+            operations.iter_mut().map(|operation| match operation {
+                Operation::Read(buf) => Operation::Read(buf),
+                Operation::Write(buf) => Operation::Write(buf),
+            }),
+            timeout,
+        )
+    }
+
+    fn transaction_iter_with<'a, O>(
+        &mut self,
+        address: Address,
+        operations: O,
+        timeout: TickType_t,
+    ) -> Result<(), EspError>
+    where
+        O: IntoIterator<Item = Operation<'a>>,
+    {
         let mut command_link = CommandLink::new()?;
 
-        let last_op_index = operations.len() - 1;
+        let mut operations = operations.into_iter().peekable();
         let mut prev_was_read = None;
 
-        for (i, operation) in operations.iter_mut().enumerate() {
+        while let Some(operation) = operations.next() {
             match operation {
                 Operation::Read(buf) => {
                     if Some(true) != prev_was_read {
-                        command_link.master_start()?;
-                        command_link.master_write_byte(
-                            (address << 1) | (i2c_rw_t_I2C_MASTER_READ as u8),
-                            true,
-                        )?;
+                        command_link.master_address(address, true, prev_was_read.is_some())?;
                     }
                     prev_was_read = Some(true);
 
                     if !buf.is_empty() {
-                        let ack = if i == last_op_index {
+                        let ack = if operations.peek().is_none() {
                             AckType::LastNack
                         } else {
                             AckType::Ack
@@ -272,11 +700,7 @@ where
                 }
                 Operation::Write(buf) => {
                     if Some(false) != prev_was_read {
-                        command_link.master_start()?;
-                        command_link.master_write_byte(
-                            (address << 1) | (i2c_rw_t_I2C_MASTER_WRITE as u8),
-                            true,
-                        )?;
+                        command_link.master_address(address, false, prev_was_read.is_some())?;
                     }
                     prev_was_read = Some(false);
 
@@ -316,7 +740,7 @@ where
     type Error = I2cError;
 
     fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
-        I2cMasterDriver::read(self, addr, buffer, BLOCK).map_err(to_i2c_err)
+        I2cMasterDriver::read(self, addr, buffer, BLOCK).map_err(|err| self.classify(err))
     }
 }
 
@@ -327,7 +751,7 @@ where
     type Error = I2cError;
 
     fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
-        I2cMasterDriver::write(self, addr, bytes, BLOCK).map_err(to_i2c_err)
+        I2cMasterDriver::write(self, addr, bytes, BLOCK).map_err(|err| self.classify(err))
     }
 }
 
@@ -338,7 +762,8 @@ where
     type Error = I2cError;
 
     fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
-        I2cMasterDriver::write_read(self, addr, bytes, buffer, BLOCK).map_err(to_i2c_err)
+        I2cMasterDriver::write_read(self, addr, bytes, buffer, BLOCK)
+            .map_err(|err| self.classify(err))
     }
 }
 
@@ -355,34 +780,37 @@ where
     I2C: I2c,
 {
     fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
-        I2cMasterDriver::read(self, addr, buffer, BLOCK).map_err(to_i2c_err)
+        I2cMasterDriver::read(self, addr, buffer, BLOCK).map_err(|err| self.classify(err))
     }
 
     fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
-        I2cMasterDriver::write(self, addr, bytes, BLOCK).map_err(to_i2c_err)
+        I2cMasterDriver::write(self, addr, bytes, BLOCK).map_err(|err| self.classify(err))
     }
 
     fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
-        I2cMasterDriver::write_read(self, addr, bytes, buffer, BLOCK).map_err(to_i2c_err)
+        I2cMasterDriver::write_read(self, addr, bytes, buffer, BLOCK)
+            .map_err(|err| self.classify(err))
     }
 
-    fn write_iter<B>(&mut self, _address: u8, _bytes: B) -> Result<(), Self::Error>
+    fn write_iter<B>(&mut self, address: u8, bytes: B) -> Result<(), Self::Error>
     where
         B: IntoIterator<Item = u8>,
     {
-        todo!()
+        self.write_iter_to(Address::SevenBit(address), bytes, BLOCK)
+            .map_err(|err| self.classify(err))
     }
 
     fn write_iter_read<B>(
         &mut self,
-        _address: u8,
-        _bytes: B,
-        _buffer: &mut [u8],
+        address: u8,
+        bytes: B,
+        buffer: &mut [u8],
     ) -> Result<(), Self::Error>
     where
         B: IntoIterator<Item = u8>,
     {
-        todo!()
+        self.write_iter_read_to(Address::SevenBit(address), bytes, buffer, BLOCK)
+            .map_err(|err| self.classify(err))
     }
 
     fn transaction<'a>(
@@ -390,14 +818,84 @@ where
         address: u8,
         operations: &mut [embedded_hal::i2c::Operation<'a>],
     ) -> Result<(), Self::Error> {
-        I2cMasterDriver::transaction(self, address, operations, BLOCK).map_err(to_i2c_err)
+        I2cMasterDriver::transaction(self, address, operations, BLOCK)
+            .map_err(|err| self.classify(err))
+    }
+
+    fn transaction_iter<'a, O>(&mut self, address: u8, operations: O) -> Result<(), Self::Error>
+    where
+        O: IntoIterator<Item = embedded_hal::i2c::Operation<'a>>,
+    {
+        self.transaction_iter_with(Address::SevenBit(address), operations, BLOCK)
+            .map_err(|err| self.classify(err))
+    }
+}
+
+impl<'d, I2C> embedded_hal::i2c::I2c<embedded_hal::i2c::TenBitAddress> for I2cMasterDriver<'d, I2C>
+where
+    I2C: I2c,
+{
+    fn read(&mut self, addr: u16, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        I2cMasterDriver::read_ten_bit(self, addr, buffer, BLOCK).map_err(|err| self.classify(err))
+    }
+
+    fn write(&mut self, addr: u16, bytes: &[u8]) -> Result<(), Self::Error> {
+        I2cMasterDriver::write_ten_bit(self, addr, bytes, BLOCK).map_err(|err| self.classify(err))
+    }
+
+    fn write_read(
+        &mut self,
+        addr: u16,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        I2cMasterDriver::write_read_ten_bit(self, addr, bytes, buffer, BLOCK)
+            .map_err(|err| self.classify(err))
+    }
+
+    fn write_iter<B>(&mut self, address: u16, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.write_iter_to(Address::TenBit(address), bytes, BLOCK)
+            .map_err(|err| self.classify(err))
+    }
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: u16,
+        bytes: B,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.write_iter_read_to(Address::TenBit(address), bytes, buffer, BLOCK)
+            .map_err(|err| self.classify(err))
+    }
+
+    fn transaction<'a>(
+        &mut self,
+        address: u16,
+        operations: &mut [embedded_hal::i2c::Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        I2cMasterDriver::transaction_ten_bit(self, address, operations, BLOCK)
+            .map_err(|err| self.classify(err))
     }
 
-    fn transaction_iter<'a, O>(&mut self, _address: u8, _operations: O) -> Result<(), Self::Error>
+    fn transaction_iter<'a, O>(&mut self, address: u16, operations: O) -> Result<(), Self::Error>
     where
         O: IntoIterator<Item = embedded_hal::i2c::Operation<'a>>,
     {
-        todo!()
+        self.transaction_iter_with(Address::TenBit(address), operations, BLOCK)
+            .map_err(|err| self.classify(err))
+    }
+}
+
+impl I2cError {
+    /// Returns `true` if the error is due to the transfer (or a clock-stretching slave) timing out
+    pub fn is_timeout(&self) -> bool {
+        self.kind == ErrorKind::Other && self.cause.code() == ESP_ERR_TIMEOUT
     }
 }
 
@@ -472,7 +970,7 @@ where
                 i2c_mode_t_I2C_MODE_SLAVE,
                 config.rx_buf_len as u32,
                 config.tx_buf_len as u32,
-                0, // TODO: set flags
+                config.interrupt.into(),
             )
         })?;
 
@@ -514,14 +1012,355 @@ where
     }
 }
 
-#[repr(u32)]
-enum AckType {
-    Ack = i2c_ack_type_t_I2C_MASTER_ACK,
+/// Access rights of the master to a register of a `RegisterMap`
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RegisterAccess {
+    ReadWrite,
+    /// Master writes to the register are ignored
+    ReadOnly,
+    /// Master reads of the register return 0
+    WriteOnly,
+}
+
+/// A register file of `N` 8-bit registers, as exposed by an `I2cRegisterSlave` to the I2C master
+pub struct RegisterMap<const N: usize> {
+    data: [u8; N],
+    access: [RegisterAccess; N],
+}
+
+impl<const N: usize> RegisterMap<N> {
+    pub fn new() -> Self {
+        Self {
+            data: [0; N],
+            access: [RegisterAccess::ReadWrite; N],
+        }
+    }
+
+    pub fn set_access(&mut self, registers: core::ops::Range<usize>, access: RegisterAccess) {
+        for register in registers {
+            self.access[register] = access;
+        }
+    }
+
+    pub fn access(&self, register: usize) -> RegisterAccess {
+        self.access[register]
+    }
+
+    /// Returns the value of a register, regardless of its master access rights
+    pub fn get(&self, register: usize) -> u8 {
+        self.data[register]
+    }
+
+    /// Sets the value of a register, regardless of its master access rights
+    pub fn set(&mut self, register: usize, value: u8) {
+        self.data[register] = value;
+    }
+
+    pub fn data(&self) -> &[u8; N] {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [u8; N] {
+        &mut self.data
+    }
+
+    fn master_write(&mut self, register: usize, value: u8) {
+        if self.access[register] != RegisterAccess::ReadOnly {
+            self.data[register] = value;
+        }
+    }
+
+    fn master_read(&self, register: usize) -> u8 {
+        if self.access[register] != RegisterAccess::WriteOnly {
+            self.data[register]
+        } else {
+            0
+        }
+    }
+}
+
+impl<const N: usize> Default for RegisterMap<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Master activity, as reported to the handler of `I2cRegisterSlave::process`
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RegisterEvent {
+    /// The master wrote `len` bytes starting at `register`
+    Write { register: usize, len: usize },
+    /// The master set the register pointer to `register`, without writing any data,
+    /// which is what a master does before reading.
+    ///
+    /// The registers are staged for reading as soon as the register pointer is received,
+    /// because the master starts reading right away. The handler is called afterwards, so
+    /// values it updates are only observed by the master's next read.
+    Read { register: usize },
+}
+
+/// Emulates a register-based I2C device (e.g. a sensor or an EEPROM) on top of `I2cSlaveDriver`.
+///
+/// Each master write starts with the register pointer (8 or 16 bits wide), followed by
+/// the data to be written with auto-increment (wrapping around at the end of the register map).
+/// The registers starting at the register pointer are staged for reading as soon as the
+/// register pointer is received, and again after each write.
+///
+/// NOTE: The ESP-IDF I2C slave driver does not report START and STOP conditions, so
+/// transactions are delimited based on `RegisterSlaveConfig::transaction_gap` instead.
+///
+/// `N` must not be zero.
+pub struct I2cRegisterSlave<'d, I2C, const N: usize>
+where
+    I2C: I2c,
+{
+    driver: I2cSlaveDriver<'d, I2C>,
+    map: RegisterMap<N>,
+    pointer: usize,
+    config: config::RegisterSlaveConfig,
+    #[cfg(feature = "alloc")]
+    poll_timer: Option<PollTimer>,
+}
+
+/// One-shot timer waking up `I2cRegisterSlave::process_async`, created on first use
+/// and reused for all subsequent polls
+#[cfg(feature = "alloc")]
+struct PollTimer {
+    timer: crate::esp_timer::EspTimer,
+    notification: alloc::sync::Arc<crate::interrupt::asynch::HalIsrNotification>,
+}
+
+#[cfg(feature = "alloc")]
+impl PollTimer {
+    fn new() -> Result<Self, EspError> {
+        let notification =
+            alloc::sync::Arc::new(crate::interrupt::asynch::HalIsrNotification::new());
+
+        let timer = crate::esp_timer::EspTaskTimerService::new()?.timer({
+            let notification = notification.clone();
+
+            move || {
+                notification.notify_lsb();
+            }
+        })?;
+
+        Ok(Self {
+            timer,
+            notification,
+        })
+    }
+
+    async fn wait(&self, duration: Duration) -> Result<(), EspError> {
+        self.timer.after(duration)?;
+        self.notification.wait().await;
+
+        Ok(())
+    }
+}
+
+impl<'d, I2C, const N: usize> I2cRegisterSlave<'d, I2C, N>
+where
+    I2C: I2c,
+{
+    const NON_EMPTY: () = assert!(N > 0, "The register map must not be empty");
+
+    pub fn new(
+        driver: I2cSlaveDriver<'d, I2C>,
+        map: RegisterMap<N>,
+        config: &config::RegisterSlaveConfig,
+    ) -> Result<Self, EspError> {
+        let () = Self::NON_EMPTY;
+
+        let mut this = Self {
+            driver,
+            map,
+            pointer: 0,
+            config: *config,
+            #[cfg(feature = "alloc")]
+            poll_timer: None,
+        };
+
+        this.stage()?;
+
+        Ok(this)
+    }
+
+    pub fn map(&self) -> &RegisterMap<N> {
+        &self.map
+    }
+
+    /// Gives access to the register map. Call `stage` afterwards so that
+    /// pending master reads observe the updated values.
+    pub fn map_mut(&mut self) -> &mut RegisterMap<N> {
+        &mut self.map
+    }
+
+    /// Returns the current register pointer
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    /// Waits up to `timeout` for a master transaction and processes it.
+    ///
+    /// Returns `None` if no transaction arrived in time.
+    pub fn process(
+        &mut self,
+        timeout: TickType_t,
+        handler: impl FnMut(RegisterEvent, &mut RegisterMap<N>),
+    ) -> Result<Option<RegisterEvent>, EspError> {
+        let mut first = [0_u8; 1];
+
+        if self.receive(&mut first, timeout)? == 0 {
+            Ok(None)
+        } else {
+            self.process_transaction(first[0], handler).map(Some)
+        }
+    }
+
+    /// Async version of `process`, which waits for a master transaction by
+    /// polling the slave RX buffer every `RegisterSlaveConfig::poll_interval`
+    #[cfg(feature = "alloc")]
+    pub async fn process_async(
+        &mut self,
+        handler: impl FnMut(RegisterEvent, &mut RegisterMap<N>),
+    ) -> Result<RegisterEvent, EspError> {
+        let mut first = [0_u8; 1];
+
+        while self.receive(&mut first, NON_BLOCK)? == 0 {
+            if self.poll_timer.is_none() {
+                self.poll_timer = Some(PollTimer::new()?);
+            }
+
+            self.poll_timer
+                .as_ref()
+                .unwrap()
+                .wait(self.config.poll_interval)
+                .await?;
+        }
+
+        self.process_transaction(first[0], handler)
+    }
+
+    /// Stages the registers starting at the register pointer for reading by the master,
+    /// discarding any previously staged data
+    pub fn stage(&mut self) -> Result<(), EspError> {
+        // The driver's ISR moves staged data from its TX ring buffer to the hardware FIFO
+        // as soon as there is room. Staging no more than fits in the FIFO means that the
+        // ring buffer is drained right away, so resetting the FIFO discards all stale data.
+        esp!(unsafe { i2c_reset_tx_fifo(I2C::port()) })?;
+
+        let mut staged = [0_u8; TX_FIFO_LEN];
+        let len = self.config.read_ahead.min(N).min(staged.len());
+        let mut register = self.pointer;
+
+        for byte in &mut staged[..len] {
+            *byte = self.map.master_read(register);
+            register = (register + 1) % N;
+        }
+
+        let written = unsafe {
+            i2c_slave_write_buffer(
+                I2C::port(),
+                staged.as_ptr() as *mut u8,
+                len as i32,
+                NON_BLOCK,
+            )
+        };
+
+        if written < 0 {
+            Err(EspError::from(ESP_FAIL).unwrap())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn release(self) -> (I2cSlaveDriver<'d, I2C>, RegisterMap<N>) {
+        (self.driver, self.map)
+    }
+
+    fn process_transaction(
+        &mut self,
+        first: u8,
+        mut handler: impl FnMut(RegisterEvent, &mut RegisterMap<N>),
+    ) -> Result<RegisterEvent, EspError> {
+        let gap = self.config.transaction_gap;
+
+        let pointer = match self.config.address_width {
+            config::RegisterAddressWidth::Bits8 => first as usize,
+            config::RegisterAddressWidth::Bits16 => {
+                let mut low = [0_u8; 1];
+
+                if self.receive(&mut low, gap)? == 0 {
+                    // Incomplete register pointer
+                    low[0] = 0;
+                }
+
+                ((first as usize) << 8) | low[0] as usize
+            }
+        };
+
+        self.pointer = pointer % N;
+
+        // The master might start reading right after setting the register pointer
+        self.stage()?;
+
+        let register = self.pointer;
+        let mut len = 0;
+        let mut data = [0_u8; 32];
+
+        loop {
+            let received = self.receive(&mut data, gap)?;
+
+            for byte in &data[..received] {
+                self.map.master_write(self.pointer, *byte);
+                self.pointer = (self.pointer + 1) % N;
+            }
+
+            len += received;
+
+            if received < data.len() {
+                break;
+            }
+        }
+
+        let event = if len > 0 {
+            RegisterEvent::Write { register, len }
+        } else {
+            RegisterEvent::Read { register }
+        };
+
+        handler(event, &mut self.map);
+
+        if len > 0 {
+            self.stage()?;
+        }
+
+        Ok(event)
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], timeout: TickType_t) -> Result<usize, EspError> {
+        match self.driver.read(buffer, timeout) {
+            Ok(len) => Ok(len),
+            Err(err) if err.code() == ESP_ERR_TIMEOUT => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[repr(u32)]
+enum AckType {
+    Ack = i2c_ack_type_t_I2C_MASTER_ACK,
     #[allow(dead_code)]
     Nack = i2c_ack_type_t_I2C_MASTER_NACK,
     LastNack = i2c_ack_type_t_I2C_MASTER_LAST_NACK,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Address {
+    SevenBit(u8),
+    TenBit(u16),
+}
+
 struct CommandLink<'buffers>(i2c_cmd_handle_t, PhantomData<&'buffers u8>);
 
 impl<'buffers> CommandLink<'buffers> {
@@ -539,6 +1378,47 @@ impl<'buffers> CommandLink<'buffers> {
         esp!(unsafe { i2c_master_start(self.0) })
     }
 
+    /// Issues a (repeated) START condition, followed by the address header of `address`.
+    ///
+    /// For 10-bit addresses, the full two-byte header is only sent for writes, or for reads
+    /// when the slave has not been `addressed` earlier in the same transfer;
+    /// a read is then addressed by a repeated START followed by the first header byte with the R bit set.
+    fn master_address(
+        &mut self,
+        address: Address,
+        read: bool,
+        addressed: bool,
+    ) -> Result<(), EspError> {
+        let rw = if read {
+            i2c_rw_t_I2C_MASTER_READ
+        } else {
+            i2c_rw_t_I2C_MASTER_WRITE
+        } as u8;
+
+        match address {
+            Address::SevenBit(addr) => {
+                self.master_start()?;
+                self.master_write_byte((addr << 1) | rw, true)
+            }
+            Address::TenBit(addr) => {
+                let header = 0xf0 | (((addr >> 8) as u8 & 0x03) << 1);
+
+                if !read || !addressed {
+                    self.master_start()?;
+                    self.master_write_byte(header | (i2c_rw_t_I2C_MASTER_WRITE as u8), true)?;
+                    self.master_write_byte(addr as u8, true)?;
+                }
+
+                if read {
+                    self.master_start()?;
+                    self.master_write_byte(header | rw, true)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+
     fn master_stop(&mut self) -> Result<(), EspError> {
         esp!(unsafe { i2c_master_stop(self.0) })
     }
@@ -578,6 +1458,388 @@ impl<'buffers> Drop for CommandLink<'buffers> {
     }
 }
 
+/// Sharing of a single `I2cMasterDriver` between multiple device drivers, possibly running in different threads.
+///
+/// The bus is wrapped in an `I2cBus`, which serializes the access to the driver with a `BusMutex`,
+/// and hands out `I2cDevice` instances, each of which implements the `embedded-hal` I2C traits.
+pub mod bus {
+    use core::cell::UnsafeCell;
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    use esp_idf_sys::*;
+
+    use super::{Address, I2c, I2cError, I2cMasterDriver, Operation};
+
+    /// A lock serializing the access to a shared `I2cBus`
+    pub trait BusMutex {
+        fn lock<R>(&self, f: impl FnOnce() -> R) -> R;
+    }
+
+    /// Locks the bus with the (recursive) FreeRTOS mutex behind `task::CriticalSection`.
+    ///
+    /// NOTE: Unlike a critical section based on disabling interrupts, this one lets the
+    /// interrupt-driven ESP-IDF I2C driver complete the transfers while the bus is locked.
+    impl BusMutex for crate::task::CriticalSection {
+        fn lock<R>(&self, f: impl FnOnce() -> R) -> R {
+            let _guard = self.enter();
+
+            f()
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl BusMutex for std::sync::Mutex<()> {
+        fn lock<R>(&self, f: impl FnOnce() -> R) -> R {
+            let _guard = std::sync::Mutex::lock(self).unwrap_or_else(|e| e.into_inner());
+
+            f()
+        }
+    }
+
+    /// An `I2cBus` locked with a `std::sync::Mutex`
+    #[cfg(feature = "std")]
+    pub type MutexI2cBus<'d, I2C> = I2cBus<'d, I2C, std::sync::Mutex<()>>;
+
+    /// An `I2cBus` locked with a `task::CriticalSection`
+    pub type CriticalSectionI2cBus<'d, I2C> = I2cBus<'d, I2C, crate::task::CriticalSection>;
+
+    pub struct I2cBus<'d, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        mutex: M,
+        locked: AtomicBool,
+        driver: UnsafeCell<I2cMasterDriver<'d, I2C>>,
+    }
+
+    impl<'d, I2C, M> I2cBus<'d, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        pub fn new(driver: I2cMasterDriver<'d, I2C>) -> Self
+        where
+            M: Default,
+        {
+            Self::wrap(driver, Default::default())
+        }
+
+        pub fn wrap(driver: I2cMasterDriver<'d, I2C>, mutex: M) -> Self {
+            Self {
+                mutex,
+                locked: AtomicBool::new(false),
+                driver: UnsafeCell::new(driver),
+            }
+        }
+
+        /// Creates a new device on the bus, with `timeout` used for all its transfers
+        pub fn device(&self, timeout: TickType_t) -> I2cDevice<'_, 'd, I2C, M> {
+            I2cDevice { bus: self, timeout }
+        }
+
+        /// Runs `f` with exclusive access to the bus driver, e.g. for a `scan` or a bus `recover`.
+        ///
+        /// # Panics
+        ///
+        /// If called from within `f` - directly or by using one of the devices of this bus -
+        /// as the bus mutex might be recursive and would then hand out the driver twice.
+        pub fn lock<R>(&self, f: impl FnOnce(&mut I2cMasterDriver<'d, I2C>) -> R) -> R {
+            self.mutex.lock(|| {
+                // With the bus mutex held, only the current thread can have set the flag
+                if self.locked.swap(true, Ordering::Acquire) {
+                    panic!("I2cBus is already locked by the current thread");
+                }
+
+                let _unlock = Unlock(&self.locked);
+
+                f(unsafe { self.driver.get().as_mut().unwrap() })
+            })
+        }
+
+        pub fn release(self) -> I2cMasterDriver<'d, I2C> {
+            self.driver.into_inner()
+        }
+    }
+
+    struct Unlock<'a>(&'a AtomicBool);
+
+    impl<'a> Drop for Unlock<'a> {
+        fn drop(&mut self) {
+            self.0.store(false, Ordering::Release);
+        }
+    }
+
+    unsafe impl<'d, I2C, M> Send for I2cBus<'d, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex + Send,
+    {
+    }
+
+    unsafe impl<'d, I2C, M> Sync for I2cBus<'d, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex + Sync,
+    {
+    }
+
+    /// A device on a shared `I2cBus`.
+    ///
+    /// Each transfer locks the bus for its whole duration, so transfers of different
+    /// devices are never interleaved.
+    pub struct I2cDevice<'b, 'd, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        bus: &'b I2cBus<'d, I2C, M>,
+        timeout: TickType_t,
+    }
+
+    impl<'b, 'd, I2C, M> I2cDevice<'b, 'd, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        pub fn timeout(&self) -> TickType_t {
+            self.timeout
+        }
+
+        pub fn set_timeout(&mut self, timeout: TickType_t) {
+            self.timeout = timeout;
+        }
+
+        fn transfer(
+            &mut self,
+            f: impl FnOnce(&mut I2cMasterDriver<'d, I2C>, TickType_t) -> Result<(), EspError>,
+        ) -> Result<(), I2cError> {
+            let timeout = self.timeout;
+
+            self.bus
+                .lock(|driver| f(driver, timeout).map_err(|err| driver.classify(err)))
+        }
+    }
+
+    impl<'b, 'd, I2C, M> Clone for I2cDevice<'b, 'd, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        fn clone(&self) -> Self {
+            Self {
+                bus: self.bus,
+                timeout: self.timeout,
+            }
+        }
+    }
+
+    impl<'b, 'd, I2C, M> embedded_hal_0_2::blocking::i2c::Read for I2cDevice<'b, 'd, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        type Error = I2cError;
+
+        fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| driver.read(addr, buffer, timeout))
+        }
+    }
+
+    impl<'b, 'd, I2C, M> embedded_hal_0_2::blocking::i2c::Write for I2cDevice<'b, 'd, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        type Error = I2cError;
+
+        fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| driver.write(addr, bytes, timeout))
+        }
+    }
+
+    impl<'b, 'd, I2C, M> embedded_hal_0_2::blocking::i2c::WriteRead for I2cDevice<'b, 'd, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        type Error = I2cError;
+
+        fn write_read(
+            &mut self,
+            addr: u8,
+            bytes: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| driver.write_read(addr, bytes, buffer, timeout))
+        }
+    }
+
+    impl<'b, 'd, I2C, M> embedded_hal::i2c::ErrorType for I2cDevice<'b, 'd, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        type Error = I2cError;
+    }
+
+    impl<'b, 'd, I2C, M> embedded_hal::i2c::I2c<embedded_hal::i2c::SevenBitAddress>
+        for I2cDevice<'b, 'd, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| driver.read(addr, buffer, timeout))
+        }
+
+        fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| driver.write(addr, bytes, timeout))
+        }
+
+        fn write_read(
+            &mut self,
+            addr: u8,
+            bytes: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| driver.write_read(addr, bytes, buffer, timeout))
+        }
+
+        fn write_iter<B>(&mut self, address: u8, bytes: B) -> Result<(), Self::Error>
+        where
+            B: IntoIterator<Item = u8>,
+        {
+            self.transfer(|driver, timeout| {
+                driver.write_iter_to(Address::SevenBit(address), bytes, timeout)
+            })
+        }
+
+        fn write_iter_read<B>(
+            &mut self,
+            address: u8,
+            bytes: B,
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error>
+        where
+            B: IntoIterator<Item = u8>,
+        {
+            self.transfer(|driver, timeout| {
+                driver.write_iter_read_to(Address::SevenBit(address), bytes, buffer, timeout)
+            })
+        }
+
+        fn transaction<'a>(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'a>],
+        ) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| driver.transaction(address, operations, timeout))
+        }
+
+        fn transaction_iter<'a, O>(&mut self, address: u8, operations: O) -> Result<(), Self::Error>
+        where
+            O: IntoIterator<Item = Operation<'a>>,
+        {
+            self.transfer(|driver, timeout| {
+                driver.transaction_iter_with(Address::SevenBit(address), operations, timeout)
+            })
+        }
+    }
+
+    impl<'b, 'd, I2C, M> embedded_hal::i2c::I2c<embedded_hal::i2c::TenBitAddress>
+        for I2cDevice<'b, 'd, I2C, M>
+    where
+        I2C: I2c,
+        M: BusMutex,
+    {
+        fn read(&mut self, addr: u16, buffer: &mut [u8]) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| driver.read_ten_bit(addr, buffer, timeout))
+        }
+
+        fn write(&mut self, addr: u16, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| driver.write_ten_bit(addr, bytes, timeout))
+        }
+
+        fn write_read(
+            &mut self,
+            addr: u16,
+            bytes: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| driver.write_read_ten_bit(addr, bytes, buffer, timeout))
+        }
+
+        fn write_iter<B>(&mut self, address: u16, bytes: B) -> Result<(), Self::Error>
+        where
+            B: IntoIterator<Item = u8>,
+        {
+            self.transfer(|driver, timeout| {
+                driver.write_iter_to(Address::TenBit(address), bytes, timeout)
+            })
+        }
+
+        fn write_iter_read<B>(
+            &mut self,
+            address: u16,
+            bytes: B,
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error>
+        where
+            B: IntoIterator<Item = u8>,
+        {
+            self.transfer(|driver, timeout| {
+                driver.write_iter_read_to(Address::TenBit(address), bytes, buffer, timeout)
+            })
+        }
+
+        fn transaction<'a>(
+            &mut self,
+            address: u16,
+            operations: &mut [Operation<'a>],
+        ) -> Result<(), Self::Error> {
+            self.transfer(|driver, timeout| {
+                driver.transaction_ten_bit(address, operations, timeout)
+            })
+        }
+
+        fn transaction_iter<'a, O>(
+            &mut self,
+            address: u16,
+            operations: O,
+        ) -> Result<(), Self::Error>
+        where
+            O: IntoIterator<Item = Operation<'a>>,
+        {
+            self.transfer(|driver, timeout| {
+                driver.transaction_iter_with(Address::TenBit(address), operations, timeout)
+            })
+        }
+    }
+}
+
+mod regs {
+    use esp_idf_sys::*;
+
+    #[cfg(esp32)]
+    const SR: usize = 0x04;
+    #[cfg(not(esp32))]
+    const SR: usize = 0x08;
+
+    const SR_ARB_LOST: u32 = 1 << 3;
+
+    /// Whether the controller of `port` lost arbitration during its last transfer
+    pub fn arbitration_lost(port: i2c_port_t) -> bool {
+        let base = match port {
+            #[cfg(not(esp32c3))]
+            1 => DR_REG_I2C1_EXT_BASE,
+            _ => DR_REG_I2C_EXT_BASE,
+        } as usize;
+
+        unsafe { core::ptr::read_volatile((base + SR) as *const u32) & SR_ARB_LOST != 0 }
+    }
+}
+
 macro_rules! impl_i2c {
     ($i2c:ident: $port:expr) => {
         crate::impl_peripheral!($i2c);
@@ -2,6 +2,12 @@ use core::sync::atomic::{AtomicU64, Ordering};
 
 use esp_idf_sys::*;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+
 pub(crate) static CS: CriticalSection = CriticalSection::new();
 
 /// Returns true if the currently active core is executing an ISR request
@@ -221,6 +227,201 @@ pub fn free<R>(f: impl FnOnce() -> R) -> R {
     f()
 }
 
+/// Interrupt priority level
+///
+/// Only levels 1 to 3 can be serviced by handlers written in Rust (or C);
+/// higher levels require assembly handlers and are therefore not supported here.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InterruptLevel {
+    Level1,
+    Level2,
+    Level3,
+}
+
+impl From<InterruptLevel> for u32 {
+    fn from(level: InterruptLevel) -> Self {
+        match level {
+            InterruptLevel::Level1 => ESP_INTR_FLAG_LEVEL1,
+            InterruptLevel::Level2 => ESP_INTR_FLAG_LEVEL2,
+            InterruptLevel::Level3 => ESP_INTR_FLAG_LEVEL3,
+        }
+    }
+}
+
+/// Interrupt allocation configuration, accepted by the configurations of all drivers
+/// which install an interrupt handler, as well as by `allocate` for custom peripherals.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct InterruptConfig {
+    /// The priority level of the interrupt; `None` lets the allocator choose any low or medium level
+    pub level: Option<InterruptLevel>,
+    /// Allow the interrupt to be shared with other peripherals
+    pub shared: bool,
+    /// The handler is IRAM-safe and can run while the flash cache is disabled
+    pub iram: bool,
+    /// Edge-triggered rather than level-triggered interrupt
+    pub edge: bool,
+}
+
+impl InterruptConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[must_use]
+    pub fn level(mut self, level: Option<InterruptLevel>) -> Self {
+        self.level = level;
+        self
+    }
+
+    #[must_use]
+    pub fn shared(mut self, shared: bool) -> Self {
+        self.shared = shared;
+        self
+    }
+
+    #[must_use]
+    pub fn iram(mut self, iram: bool) -> Self {
+        self.iram = iram;
+        self
+    }
+
+    #[must_use]
+    pub fn edge(mut self, edge: bool) -> Self {
+        self.edge = edge;
+        self
+    }
+
+    /// Returns the `ESP_INTR_FLAG_*` flags corresponding to this configuration
+    pub fn flags(&self) -> u32 {
+        let mut flags = self.level.map(Into::into).unwrap_or(0);
+
+        if self.shared {
+            flags |= ESP_INTR_FLAG_SHARED;
+        }
+
+        if self.iram {
+            flags |= ESP_INTR_FLAG_IRAM;
+        }
+
+        if self.edge {
+            flags |= ESP_INTR_FLAG_EDGE;
+        }
+
+        flags
+    }
+}
+
+impl From<InterruptConfig> for i32 {
+    fn from(config: InterruptConfig) -> Self {
+        config.flags() as _
+    }
+}
+
+/// Allocates an interrupt for the peripheral interrupt `source` (one of the `ETS_*_INTR_SOURCE` values)
+/// and installs `handler` for it.
+///
+/// The returned `Interrupt` handle frees the interrupt when dropped.
+///
+/// # Safety
+///
+/// Care should be taken not to call STD, libc or FreeRTOS APIs (except for a few allowed ones)
+/// in the handler passed to this function, as it is executed in an ISR context.
+///
+/// Moreover, the handler is not placed in IRAM, so `InterruptConfig::iram` should
+/// only be set if the handler itself takes care not to run from flash.
+#[cfg(feature = "alloc")]
+pub unsafe fn allocate(
+    source: u32,
+    config: &InterruptConfig,
+    handler: impl FnMut() + Send + 'static,
+) -> Result<Interrupt, EspError> {
+    let mut handler: Box<Box<dyn FnMut() + Send + 'static>> = Box::new(Box::new(handler));
+
+    let mut handle: intr_handle_t = core::ptr::null_mut();
+
+    esp!(esp_intr_alloc(
+        source as _,
+        config.flags() as _,
+        Some(Interrupt::handle_isr),
+        handler.as_mut() as *mut _ as *mut _,
+        &mut handle as *mut _,
+    ))?;
+
+    Ok(Interrupt {
+        handle,
+        _handler: handler,
+    })
+}
+
+/// An allocated interrupt, as returned by `allocate`
+#[cfg(feature = "alloc")]
+pub struct Interrupt {
+    handle: intr_handle_t,
+    _handler: Box<Box<dyn FnMut() + Send + 'static>>,
+}
+
+#[cfg(feature = "alloc")]
+impl Interrupt {
+    pub fn enable(&mut self) -> Result<(), EspError> {
+        esp!(unsafe { esp_intr_enable(self.handle) })
+    }
+
+    pub fn disable(&mut self) -> Result<(), EspError> {
+        esp!(unsafe { esp_intr_disable(self.handle) })
+    }
+
+    /// Returns the CPU core on which the interrupt is serviced
+    pub fn core(&self) -> crate::cpu::Core {
+        unsafe { esp_intr_get_cpu(self.handle) }.into()
+    }
+
+    /// Returns the CPU interrupt number allocated for the interrupt
+    pub fn number(&self) -> i32 {
+        unsafe { esp_intr_get_intno(self.handle) }
+    }
+
+    pub fn handle(&self) -> intr_handle_t {
+        self.handle
+    }
+
+    /// Frees the interrupt; same as dropping the handle, but reports errors
+    pub fn free(self) -> Result<(), EspError> {
+        let mut this = core::mem::ManuallyDrop::new(self);
+
+        let result = esp!(unsafe { esp_intr_free(this.handle) });
+
+        if result.is_ok() {
+            // The handler is no longer installed, so it is now safe to drop it.
+            // Otherwise, leak it, as the ISR might still call into it
+            unsafe {
+                core::ptr::drop_in_place(&mut this._handler);
+            }
+        }
+
+        result
+    }
+
+    unsafe extern "C" fn handle_isr(arg: *mut c_types::c_void) {
+        let handler = (arg as *mut Box<dyn FnMut() + Send + 'static>)
+            .as_mut()
+            .unwrap();
+
+        if with_isr_yield_signal(handler) {
+            crate::task::do_yield();
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for Interrupt {
+    fn drop(&mut self) {
+        esp!(unsafe { esp_intr_free(self.handle) }).unwrap();
+    }
+}
+
+#[cfg(feature = "alloc")]
+unsafe impl Send for Interrupt {}
+
 #[cfg(feature = "embassy-sync")]
 pub mod embassy_sync {
     use core::marker::PhantomData;
@@ -255,3 +456,120 @@ pub mod embassy_sync {
         }
     }
 }
+
+pub mod asynch {
+    use core::cell::UnsafeCell;
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use core::task::{Context, Poll, Waker};
+
+    use super::CriticalSection;
+
+    /// Single-slot signaling primitive which can be notified from an ISR context
+    /// and awaited from an async task.
+    ///
+    /// The notification value is a `u32` bit-set; multiple notifications that arrive
+    /// before the waiting task is polled are OR-ed together.
+    ///
+    /// NOTE: The waker registered by the awaiting task is woken from within the ISR,
+    /// so the executor used should support being woken from an ISR context
+    /// (e.g. `edge-executor` with `FreeRtosMonitor`).
+    pub struct HalIsrNotification {
+        waker: UnsafeCell<Option<Waker>>,
+        cs: CriticalSection,
+        notified: AtomicU32,
+    }
+
+    impl HalIsrNotification {
+        /// Creates a new `HalIsrNotification`
+        pub const fn new() -> Self {
+            Self {
+                waker: UnsafeCell::new(None),
+                cs: CriticalSection::new(),
+                notified: AtomicU32::new(0),
+            }
+        }
+
+        /// Clears any pending notification
+        pub fn reset(&self) {
+            self.notified.store(0, Ordering::SeqCst);
+        }
+
+        /// Notifies the awaiting task (if any) with the supplied bits.
+        ///
+        /// Returns `true` if there was a registered waker which got woken.
+        #[link_section = ".iram1.interrupt_hal_isr_notification_notify"]
+        pub fn notify(&self, bits: u32) -> bool {
+            if bits == 0 {
+                return false;
+            }
+
+            self.notified.fetch_or(bits, Ordering::SeqCst);
+
+            let waker = {
+                let _guard = self.cs.enter();
+
+                unsafe { self.waker.get().as_mut().unwrap().take() }
+            };
+
+            if let Some(waker) = waker {
+                waker.wake();
+
+                true
+            } else {
+                false
+            }
+        }
+
+        /// Notifies the awaiting task with bit 0 set
+        pub fn notify_lsb(&self) -> bool {
+            self.notify(1)
+        }
+
+        /// Waits for a notification and returns (and clears) the notified bits
+        pub async fn wait(&self) -> u32 {
+            NotificationFuture(self).await
+        }
+
+        pub fn poll_wait(&self, cx: &Context<'_>) -> Poll<u32> {
+            {
+                let _guard = self.cs.enter();
+
+                let waker = unsafe { self.waker.get().as_mut().unwrap() };
+
+                match waker {
+                    Some(waker) if waker.will_wake(cx.waker()) => (),
+                    _ => *waker = Some(cx.waker().clone()),
+                }
+            }
+
+            let bits = self.notified.swap(0, Ordering::SeqCst);
+
+            if bits != 0 {
+                Poll::Ready(bits)
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    impl Default for HalIsrNotification {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    unsafe impl Send for HalIsrNotification {}
+    unsafe impl Sync for HalIsrNotification {}
+
+    struct NotificationFuture<'a>(&'a HalIsrNotification);
+
+    impl<'a> Future for NotificationFuture<'a> {
+        type Output = u32;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            self.0.poll_wait(cx)
+        }
+    }
+}
@@ -15,8 +15,61 @@ compile_error!("esp-idf-hal requires the `driver` ESP-IDF component to be enable
 ))]
 compile_error!("Enabling feature `riscv-ulp-hal` implies no other feature is enabled");
 
-#[cfg(all(feature = "riscv-ulp-hal", not(esp32s2)))]
-compile_error!("Feature `riscv-ulp-hal` is currently only supported on esp32s2");
+#[cfg(all(
+    feature = "embassy-time-isr-queue",
+    not(any(
+        feature = "embassy-time-isr-queue-timer00",
+        feature = "embassy-time-isr-queue-timer01",
+        feature = "embassy-time-isr-queue-timer10",
+        feature = "embassy-time-isr-queue-timer11"
+    ))
+))]
+compile_error!("Feature `embassy-time-isr-queue` requires one of the `embassy-time-isr-queue-timerXX` features to be enabled");
+
+#[cfg(any(
+    all(
+        feature = "embassy-time-isr-queue-timer00",
+        any(
+            feature = "embassy-time-isr-queue-timer01",
+            feature = "embassy-time-isr-queue-timer10",
+            feature = "embassy-time-isr-queue-timer11"
+        )
+    ),
+    all(
+        feature = "embassy-time-isr-queue-timer01",
+        any(
+            feature = "embassy-time-isr-queue-timer10",
+            feature = "embassy-time-isr-queue-timer11"
+        )
+    ),
+    all(
+        feature = "embassy-time-isr-queue-timer10",
+        feature = "embassy-time-isr-queue-timer11"
+    )
+))]
+compile_error!("Only one of the `embassy-time-isr-queue-timerXX` features can be enabled");
+
+#[cfg(all(feature = "embassy-time-isr-queue", feature = "embassy-time-driver"))]
+compile_error!(
+    "Features `embassy-time-isr-queue` and `embassy-time-driver` are mutually exclusive"
+);
+
+#[cfg(all(
+    esp32c3,
+    any(
+        feature = "embassy-time-isr-queue-timer01",
+        feature = "embassy-time-isr-queue-timer11"
+    )
+))]
+compile_error!("esp32c3 only has timers TIMER00 and TIMER10");
+
+#[cfg(all(feature = "riscv-ulp-hal", not(any(esp32s2, esp32s3))))]
+compile_error!("Feature `riscv-ulp-hal` is currently only supported on esp32s2 and esp32s3");
+
+// Used by the exported macros
+#[cfg(not(feature = "riscv-ulp-hal"))]
+#[doc(hidden)]
+pub use esp_idf_sys as sys;
 
 #[macro_use]
 pub mod riscv_ulp_hal;
@@ -28,6 +81,20 @@ pub mod can;
 pub mod cpu;
 #[cfg(not(feature = "riscv-ulp-hal"))]
 pub mod delay;
+#[cfg(not(feature = "riscv-ulp-hal"))]
+pub mod esp_timer;
+#[cfg(all(
+    not(feature = "riscv-ulp-hal"),
+    feature = "alloc",
+    any(
+        all(esp32, esp_idf_eth_use_esp32_emac),
+        esp_idf_eth_use_openeth,
+        esp_idf_eth_spi_ethernet_w5500,
+        esp_idf_eth_spi_ethernet_dm9051,
+        esp_idf_eth_spi_ethernet_ksz8851snl
+    )
+))]
+pub mod eth;
 pub mod gpio;
 #[cfg(all(esp32, esp_idf_version_major = "4"))]
 pub mod hall;
@@ -52,9 +119,15 @@ pub mod reset;
 #[cfg(not(feature = "riscv-ulp-hal"))]
 pub mod rmt;
 #[cfg(not(feature = "riscv-ulp-hal"))]
+pub mod rng;
+#[cfg(all(not(feature = "riscv-ulp-hal"), esp_idf_comp_sdmmc_enabled))]
+pub mod sdmmc;
+#[cfg(not(feature = "riscv-ulp-hal"))]
 pub mod spi;
 #[cfg(not(feature = "riscv-ulp-hal"))]
 pub mod task;
+#[cfg(all(any(esp32s2, esp32s3, esp32c3), not(feature = "riscv-ulp-hal")))]
+pub mod temp_sensor;
 #[cfg(not(feature = "riscv-ulp-hal"))]
 pub mod timer;
 #[cfg(not(feature = "riscv-ulp-hal"))]
@@ -62,9 +135,19 @@ pub mod uart;
 #[cfg(all(any(esp32, esp32s2, esp32s3), not(feature = "riscv-ulp-hal")))]
 pub mod ulp;
 pub mod units;
+#[cfg(all(any(esp32s2, esp32s3), not(feature = "riscv-ulp-hal")))]
+pub mod usb_otg;
+#[cfg(all(
+    any(esp32s3, esp32c3),
+    not(feature = "riscv-ulp-hal"),
+    not(esp_idf_version = "4.3")
+))]
+pub mod usb_serial;
 
 #[cfg(feature = "riscv-ulp-hal")]
 pub use crate::riscv_ulp_hal::delay;
+#[cfg(feature = "riscv-ulp-hal")]
+pub use crate::riscv_ulp_hal::i2c;
 
 // This is used to create `embedded_hal` compatible error structs
 // that preserve original `EspError`.
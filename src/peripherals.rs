@@ -2,7 +2,6 @@ use crate::adc;
 #[cfg(not(feature = "riscv-ulp-hal"))]
 use crate::can;
 use crate::gpio;
-#[cfg(not(feature = "riscv-ulp-hal"))]
 use crate::i2c;
 #[cfg(not(feature = "riscv-ulp-hal"))]
 use crate::ledc;
@@ -13,10 +12,20 @@ use crate::ledc;
 use crate::mac;
 #[cfg(not(feature = "riscv-ulp-hal"))]
 use crate::modem;
+#[cfg(feature = "riscv-ulp-hal")]
+use crate::riscv_ulp_hal::temp_sensor;
 #[cfg(not(feature = "riscv-ulp-hal"))]
 use crate::rmt;
+#[cfg(all(
+    any(esp32, esp32s3),
+    not(feature = "riscv-ulp-hal"),
+    esp_idf_comp_sdmmc_enabled
+))]
+use crate::sdmmc;
 #[cfg(not(feature = "riscv-ulp-hal"))]
 use crate::spi;
+#[cfg(all(any(esp32s2, esp32s3, esp32c3), not(feature = "riscv-ulp-hal")))]
+use crate::temp_sensor;
 #[cfg(not(feature = "riscv-ulp-hal"))]
 use crate::timer;
 #[cfg(not(feature = "riscv-ulp-hal"))]
@@ -27,6 +36,14 @@ use crate::uart;
     esp_idf_comp_ulp_enabled
 ))]
 use crate::ulp;
+#[cfg(all(any(esp32s2, esp32s3), not(feature = "riscv-ulp-hal")))]
+use crate::usb_otg;
+#[cfg(all(
+    any(esp32s3, esp32c3),
+    not(feature = "riscv-ulp-hal"),
+    not(esp_idf_version = "4.3")
+))]
+use crate::usb_serial;
 
 pub struct Peripherals {
     pub pins: gpio::Pins,
@@ -40,6 +57,8 @@ pub struct Peripherals {
     pub i2c0: i2c::I2C0,
     #[cfg(all(not(esp32c3), not(feature = "riscv-ulp-hal")))]
     pub i2c1: i2c::I2C1,
+    #[cfg(feature = "riscv-ulp-hal")]
+    pub rtc_i2c: i2c::RTC_I2C,
     #[cfg(not(feature = "riscv-ulp-hal"))]
     pub spi1: spi::SPI1,
     #[cfg(not(feature = "riscv-ulp-hal"))]
@@ -69,6 +88,22 @@ pub struct Peripherals {
     pub mac: mac::MAC,
     #[cfg(not(feature = "riscv-ulp-hal"))]
     pub modem: modem::Modem,
+    #[cfg(all(
+        any(esp32, esp32s3),
+        not(feature = "riscv-ulp-hal"),
+        esp_idf_comp_sdmmc_enabled
+    ))]
+    pub sdmmc: sdmmc::SDMMC,
+    #[cfg(any(esp32s2, esp32s3, esp32c3))]
+    pub temp_sensor: temp_sensor::TEMP_SENSOR,
+    #[cfg(all(
+        any(esp32s3, esp32c3),
+        not(feature = "riscv-ulp-hal"),
+        not(esp_idf_version = "4.3")
+    ))]
+    pub usb_serial: usb_serial::USB_SERIAL,
+    #[cfg(all(any(esp32s2, esp32s3), not(feature = "riscv-ulp-hal")))]
+    pub usb_otg: usb_otg::USB_OTG,
     #[cfg(all(
         not(feature = "riscv-ulp-hal"),
         not(feature = "embassy-time-isr-queue-timer00")
@@ -148,6 +183,8 @@ impl Peripherals {
             i2c0: i2c::I2C0::new(),
             #[cfg(all(not(esp32c3), not(feature = "riscv-ulp-hal")))]
             i2c1: i2c::I2C1::new(),
+            #[cfg(feature = "riscv-ulp-hal")]
+            rtc_i2c: i2c::RTC_I2C::new(),
             #[cfg(not(feature = "riscv-ulp-hal"))]
             spi1: spi::SPI1::new(),
             #[cfg(not(feature = "riscv-ulp-hal"))]
@@ -177,6 +214,22 @@ impl Peripherals {
             mac: mac::MAC::new(),
             #[cfg(not(feature = "riscv-ulp-hal"))]
             modem: modem::Modem::new(),
+            #[cfg(all(
+                any(esp32, esp32s3),
+                not(feature = "riscv-ulp-hal"),
+                esp_idf_comp_sdmmc_enabled
+            ))]
+            sdmmc: sdmmc::SDMMC::new(),
+            #[cfg(any(esp32s2, esp32s3, esp32c3))]
+            temp_sensor: temp_sensor::TEMP_SENSOR::new(),
+            #[cfg(all(
+                any(esp32s3, esp32c3),
+                not(feature = "riscv-ulp-hal"),
+                not(esp_idf_version = "4.3")
+            ))]
+            usb_serial: usb_serial::USB_SERIAL::new(),
+            #[cfg(all(any(esp32s2, esp32s3), not(feature = "riscv-ulp-hal")))]
+            usb_otg: usb_otg::USB_OTG::new(),
             #[cfg(all(
                 not(feature = "riscv-ulp-hal"),
                 not(feature = "embassy-time-isr-queue-timer00")
@@ -9,4 +9,13 @@ pub mod sys;
 #[cfg(feature = "riscv-ulp-hal")]
 pub mod delay;
 #[cfg(feature = "riscv-ulp-hal")]
+pub mod i2c;
+#[cfg(feature = "riscv-ulp-hal")]
+pub mod interrupt;
+pub mod mailbox;
+#[cfg(feature = "riscv-ulp-hal")]
 pub mod start;
+#[cfg(feature = "riscv-ulp-hal")]
+pub mod temp_sensor;
+#[cfg(feature = "riscv-ulp-hal")]
+pub mod touch;
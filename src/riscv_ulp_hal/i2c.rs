@@ -0,0 +1,302 @@
+//! RTC I2C master support for the RiscV ULP coprocessor
+//!
+//! The ULP talks to I2C devices through the RTC I2C controller, which performs
+//! register-oriented transfers: every transaction addresses one 8-bit register of a
+//! slave and reads or writes a single byte. Multi-byte reads and writes are therefore
+//! split into one transfer per register, with the register address incremented in between.
+//!
+//! The controller is set up by the main core before the ULP program is started:
+//! it routes the RTC I2C pins, configures the bus timing and fills the slave
+//! address table (`SENS_SAR_SLAVE_ADDRx_REG`, up to 8 entries). The ULP side
+//! can only talk to devices whose address is present in that table.
+//!
+//! With the `embedded-hal` traits, the first byte written after addressing a slave is the
+//! register address, and the following bytes - written or read - go to consecutive registers.
+//! A read which is not preceded by such a write fails, as there is no register to read from.
+//! A write of just the register address transfers nothing, as the controller sends the register
+//! address as part of every transfer; it only selects the register for the reads that follow
+//! within the same transaction.
+
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource, Operation};
+
+use crate::peripheral::{Peripheral, PeripheralRef};
+
+use super::sys::*;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct I2cError {
+    kind: ErrorKind,
+    timeout: bool,
+}
+
+impl I2cError {
+    fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            timeout: false,
+        }
+    }
+
+    fn other() -> Self {
+        Self::new(ErrorKind::Other)
+    }
+
+    fn from_status(status: u32) -> Self {
+        let kind = if status & ULP_RISCV_I2C_ERR_ACK != 0 {
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown)
+        } else if status & ULP_RISCV_I2C_ERR_ARBITRATION_LOST != 0 {
+            ErrorKind::ArbitrationLoss
+        } else {
+            ErrorKind::Other
+        };
+
+        Self {
+            kind,
+            timeout: status & ULP_RISCV_I2C_ERR_TIME_OUT != 0,
+        }
+    }
+
+    /// Returns `true` if the transfer did not complete within the timeout configured by the main core
+    pub fn is_timeout(&self) -> bool {
+        self.timeout
+    }
+}
+
+impl embedded_hal::i2c::Error for I2cError {
+    fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl core::fmt::Display for I2cError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "I2cError {{ kind: {} }}", self.kind)
+    }
+}
+
+/// Master driver for the RTC I2C controller
+pub struct I2cDriver<'d> {
+    _rtc_i2c: PeripheralRef<'d, RTC_I2C>,
+}
+
+impl<'d> I2cDriver<'d> {
+    pub fn new(rtc_i2c: impl Peripheral<P = RTC_I2C> + 'd) -> Self {
+        crate::into_ref!(rtc_i2c);
+
+        Self { _rtc_i2c: rtc_i2c }
+    }
+
+    /// Reads the 8-bit register `register` of the slave at `address`
+    pub fn read_register(&mut self, address: u8, register: u8) -> Result<u8, I2cError> {
+        let slave_sel = Self::slave_sel(address)?;
+
+        Self::check(unsafe { ulp_riscv_i2c_transfer(slave_sel, register, 0, false) })?;
+
+        Ok(unsafe { ulp_riscv_i2c_read_data() })
+    }
+
+    /// Writes `value` to the 8-bit register `register` of the slave at `address`
+    pub fn write_register(&mut self, address: u8, register: u8, value: u8) -> Result<(), I2cError> {
+        let slave_sel = Self::slave_sel(address)?;
+
+        Self::check(unsafe { ulp_riscv_i2c_transfer(slave_sel, register, value, true) })
+    }
+
+    /// Reads consecutive registers of the slave at `address`, starting at `register`
+    pub fn read_registers(
+        &mut self,
+        address: u8,
+        register: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), I2cError> {
+        for (offset, byte) in buffer.iter_mut().enumerate() {
+            *byte = self.read_register(address, register.wrapping_add(offset as u8))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes consecutive registers of the slave at `address`, starting at `register`
+    pub fn write_registers(
+        &mut self,
+        address: u8,
+        register: u8,
+        bytes: &[u8],
+    ) -> Result<(), I2cError> {
+        for (offset, byte) in bytes.iter().enumerate() {
+            self.write_register(address, register.wrapping_add(offset as u8), *byte)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `bytes`, the first of which sets `register` if it is not set yet
+    fn write_at<B>(
+        &mut self,
+        address: u8,
+        register: &mut Option<u8>,
+        bytes: B,
+    ) -> Result<(), I2cError>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        for byte in bytes {
+            match register {
+                Some(register) => {
+                    self.write_register(address, *register, byte)?;
+                    *register = register.wrapping_add(1);
+                }
+                None => *register = Some(byte),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads into `buffer`, starting at `register`
+    fn read_at(
+        &mut self,
+        address: u8,
+        register: &mut Option<u8>,
+        buffer: &mut [u8],
+    ) -> Result<(), I2cError> {
+        let start = register.ok_or_else(I2cError::other)?;
+
+        self.read_registers(address, start, buffer)?;
+        *register = Some(start.wrapping_add(buffer.len() as u8));
+
+        Ok(())
+    }
+
+    fn slave_sel(address: u8) -> Result<u32, I2cError> {
+        (0..ULP_RISCV_I2C_SLAVE_ADDR_NUM)
+            .find(|slave_sel| unsafe { ulp_riscv_i2c_get_slave_addr(*slave_sel) } == address as u16)
+            .ok_or_else(|| I2cError::new(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)))
+    }
+
+    fn check(status: u32) -> Result<(), I2cError> {
+        if status == 0 {
+            Ok(())
+        } else {
+            Err(I2cError::from_status(status))
+        }
+    }
+}
+
+/// The first byte of `bytes` is the register address, the remaining bytes are written
+/// to consecutive registers starting there.
+///
+/// Writing just the register address transfers nothing, and an empty `bytes` fails,
+/// as there is no register to write to.
+impl<'d> embedded_hal_0_2::blocking::i2c::Write for I2cDriver<'d> {
+    type Error = I2cError;
+
+    fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        match bytes.split_first() {
+            Some((register, data)) => self.write_registers(addr, *register, data),
+            None => Err(I2cError::other()),
+        }
+    }
+}
+
+/// `bytes` must contain exactly one byte - the address of the first register to read
+impl<'d> embedded_hal_0_2::blocking::i2c::WriteRead for I2cDriver<'d> {
+    type Error = I2cError;
+
+    fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        match bytes {
+            [register] => self.read_registers(addr, *register, buffer),
+            _ => Err(I2cError::other()),
+        }
+    }
+}
+
+impl<'d> embedded_hal::i2c::ErrorType for I2cDriver<'d> {
+    type Error = I2cError;
+}
+
+impl<'d> embedded_hal::i2c::I2c<embedded_hal::i2c::SevenBitAddress> for I2cDriver<'d> {
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_at(address, &mut None, buffer)
+    }
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.write_at(address, &mut None, bytes.iter().copied())
+    }
+
+    fn write_iter<B>(&mut self, address: u8, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.write_at(address, &mut None, bytes)
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.write_iter_read(address, bytes.iter().copied(), buffer)
+    }
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: u8,
+        bytes: B,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let mut register = None;
+
+        self.write_at(address, &mut register, bytes)?;
+        self.read_at(address, &mut register, buffer)
+    }
+
+    fn transaction<'a>(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        self.transaction_iter(
+            address,
+            operations.iter_mut().map(|operation| match operation {
+                Operation::Read(buf) => Operation::Read(buf),
+                Operation::Write(buf) => Operation::Write(buf),
+            }),
+        )
+    }
+
+    fn transaction_iter<'a, O>(&mut self, address: u8, operations: O) -> Result<(), Self::Error>
+    where
+        O: IntoIterator<Item = Operation<'a>>,
+    {
+        let mut register = None;
+        let mut prev_was_read = false;
+
+        for operation in operations {
+            match operation {
+                Operation::Read(buf) => {
+                    self.read_at(address, &mut register, buf)?;
+                    prev_was_read = true;
+                }
+                Operation::Write(buf) => {
+                    // A write following a read re-addresses the slave, and so starts
+                    // with a new register address
+                    if prev_was_read {
+                        register = None;
+                    }
+
+                    self.write_at(address, &mut register, buf.iter().copied())?;
+                    prev_was_read = false;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+crate::impl_peripheral!(RTC_I2C);
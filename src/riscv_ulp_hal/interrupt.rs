@@ -0,0 +1,124 @@
+//! Interrupt support for the RiscV ULP coprocessor
+//!
+//! Two interrupt sources are supported:
+//! - the software interrupt, raised by the main CPU with `UlpDriver::trigger_interrupt`
+//! - RTC IO interrupts, raised by edges or levels on the RTC GPIOs
+//!
+//! Handlers are plain functions, called from the IRQ vector with all IRQs masked.
+//! After subscribing, IRQs have to be unmasked with `enable`.
+
+use crate::gpio::{InputMode, PinDriver, RTCPin};
+
+use super::sys::*;
+
+/// Number of RTC GPIOs, which is the same on the ESP32-S2 and the ESP32-S3
+const RTCIO_PIN_COUNT: usize = 22;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RtcIoInterruptType {
+    PosEdge,
+    NegEdge,
+    AnyEdge,
+    LowLevel,
+    HighLevel,
+}
+
+impl From<RtcIoInterruptType> for u32 {
+    fn from(interrupt_type: RtcIoInterruptType) -> Self {
+        match interrupt_type {
+            RtcIoInterruptType::PosEdge => RTCIO_INTR_POSEDGE,
+            RtcIoInterruptType::NegEdge => RTCIO_INTR_NEGEDGE,
+            RtcIoInterruptType::AnyEdge => RTCIO_INTR_ANYEDGE,
+            RtcIoInterruptType::LowLevel => RTCIO_INTR_LOW_LEVEL,
+            RtcIoInterruptType::HighLevel => RTCIO_INTR_HIGH_LEVEL,
+        }
+    }
+}
+
+static mut SW_HANDLER: Option<fn()> = None;
+static mut RTCIO_HANDLERS: [Option<fn()>; RTCIO_PIN_COUNT] = [None; RTCIO_PIN_COUNT];
+
+/// Unmasks all IRQs of the ULP
+pub fn enable() {
+    unsafe { ulp_riscv_maskirq(0) };
+}
+
+/// Masks all IRQs of the ULP
+pub fn disable() {
+    unsafe { ulp_riscv_maskirq(ULP_RISCV_INTR_MASK) };
+}
+
+/// Runs `f` with all IRQs masked, restoring the previous IRQ mask afterwards
+pub fn free<R>(f: impl FnOnce() -> R) -> R {
+    let mask = unsafe { ulp_riscv_maskirq(ULP_RISCV_INTR_MASK) };
+
+    let result = f();
+
+    unsafe { ulp_riscv_maskirq(mask) };
+
+    result
+}
+
+/// Calls `handler` whenever the main CPU triggers the software interrupt of the ULP
+pub fn subscribe_sw(handler: fn()) {
+    free(|| unsafe {
+        SW_HANDLER = Some(handler);
+        ulp_riscv_enable_sw_intr();
+    });
+}
+
+pub fn unsubscribe_sw() {
+    free(|| unsafe {
+        ulp_riscv_disable_sw_intr();
+        SW_HANDLER = None;
+    });
+}
+
+/// Calls `handler` whenever `interrupt_type` is detected on the RTC GPIO of `pin`
+///
+/// On the ESP32-S2 and the ESP32-S3 the RTC GPIO numbers are the same as the GPIO numbers.
+pub fn subscribe_rtcio<T: RTCPin, MODE: InputMode>(
+    pin: &PinDriver<'_, T, MODE>,
+    interrupt_type: RtcIoInterruptType,
+    handler: fn(),
+) {
+    let gpio = pin.pin();
+
+    free(|| unsafe {
+        RTCIO_HANDLERS[gpio as usize] = Some(handler);
+        ulp_riscv_gpio_set_intr_type(gpio, interrupt_type.into());
+    });
+}
+
+pub fn unsubscribe_rtcio<T: RTCPin, MODE: InputMode>(pin: &PinDriver<'_, T, MODE>) {
+    let gpio = pin.pin();
+
+    free(|| unsafe {
+        ulp_riscv_gpio_set_intr_type(gpio, RTCIO_INTR_DISABLE);
+        RTCIO_HANDLERS[gpio as usize] = None;
+    });
+}
+
+/// Dispatches the pending peripheral IRQs to the subscribed handlers;
+/// called by the trap entry point with the pending IRQs from `q1`
+pub(crate) unsafe fn dispatch(irq_status: u32) {
+    if irq_status & ULP_RISCV_PERIPHERAL_INTERRUPT == 0 {
+        return;
+    }
+
+    if ulp_riscv_take_sw_intr() {
+        if let Some(handler) = SW_HANDLER {
+            handler();
+        }
+    }
+
+    let rtcio_status = ulp_riscv_gpio_take_intr_status();
+
+    for (gpio, handler) in RTCIO_HANDLERS.iter().enumerate() {
+        if rtcio_status & (1 << gpio) != 0 {
+            if let Some(handler) = handler {
+                handler();
+            }
+        }
+    }
+}
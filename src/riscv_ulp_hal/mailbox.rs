@@ -0,0 +1,143 @@
+//! A single-producer, single-consumer mailbox in RTC slow memory, shared by the RiscV ULP
+//! coprocessor and the main CPU
+//!
+//! The mailbox is a ring buffer of `N` 32-bit words. It is defined as a global of the ULP program
+//! and is accessed from the main CPU through a `UlpVar` handle (see `UlpDriver::mailbox`), so both
+//! sides use this very same definition and agree on its layout.
+//!
+//! Only one side may `push` and only the other side may `pop`; usually the ULP pushes
+//! samples and the main CPU drains them while it is awake.
+//!
+//! Example (ULP side):
+//! ```ignore
+//! #[no_mangle]
+//! static SAMPLES: Mailbox<64> = Mailbox::new();
+//!
+//! let _ = SAMPLES.push(sample);
+//! ```
+//!
+//! Example (main CPU side):
+//! ```ignore
+//! esp_idf_hal::ulp_var!(samples: Mailbox<64> = ulp_SAMPLES);
+//!
+//! let samples = samples()?;
+//! while let Some(sample) = ulp_driver.mailbox(&samples).pop() {
+//!     // ...
+//! }
+//! ```
+
+use core::cell::UnsafeCell;
+use core::ptr::{read_volatile, write_volatile};
+use core::sync::atomic::{fence, Ordering};
+
+#[repr(C)]
+pub struct Mailbox<const N: usize> {
+    /// Free-running count of the words pushed; only written by the producer
+    head: UnsafeCell<u32>,
+    /// Free-running count of the words popped; only written by the consumer
+    tail: UnsafeCell<u32>,
+    buffer: UnsafeCell<[u32; N]>,
+}
+
+impl<const N: usize> Mailbox<N> {
+    pub const fn new() -> Self {
+        Self {
+            head: UnsafeCell::new(0),
+            tail: UnsafeCell::new(0),
+            buffer: UnsafeCell::new([0; N]),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.head().wrapping_sub(self.tail()) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() >= N
+    }
+
+    /// Appends `value` to the mailbox, or returns it back if the mailbox is full
+    ///
+    /// Must only be called by the producer side.
+    pub fn push(&self, value: u32) -> Result<(), u32> {
+        let head = self.head();
+
+        if head.wrapping_sub(self.tail()) as usize >= N {
+            return Err(value);
+        }
+
+        unsafe {
+            write_volatile(self.slot(head), value);
+            fence(Ordering::Release);
+            write_volatile(self.head.get(), head.wrapping_add(1));
+        }
+
+        Ok(())
+    }
+
+    /// Removes the oldest word from the mailbox, if any
+    ///
+    /// Must only be called by the consumer side.
+    pub fn pop(&self) -> Option<u32> {
+        let tail = self.tail();
+
+        if self.head() == tail {
+            return None;
+        }
+
+        unsafe {
+            fence(Ordering::Acquire);
+            let value = read_volatile(self.slot(tail));
+            write_volatile(self.tail.get(), tail.wrapping_add(1));
+
+            Some(value)
+        }
+    }
+
+    /// Pops words into `buf` until the mailbox is empty or `buf` is full
+    /// and returns the number of words popped
+    ///
+    /// Must only be called by the consumer side.
+    pub fn drain(&self, buf: &mut [u32]) -> usize {
+        let mut count = 0;
+
+        for slot in buf.iter_mut() {
+            match self.pop() {
+                Some(value) => *slot = value,
+                None => break,
+            }
+
+            count += 1;
+        }
+
+        count
+    }
+
+    fn head(&self) -> u32 {
+        unsafe { read_volatile(self.head.get()) }
+    }
+
+    fn tail(&self) -> u32 {
+        unsafe { read_volatile(self.tail.get()) }
+    }
+
+    fn slot(&self, index: u32) -> *mut u32 {
+        unsafe { (self.buffer.get() as *mut u32).add(index as usize % N) }
+    }
+}
+
+impl<const N: usize> Default for Mailbox<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<const N: usize> Sync for Mailbox<N> {}
@@ -1,16 +1,40 @@
 #![allow(dead_code)]
 
-/// This module is a manual translation of a bunch of C files from current ESP-IDF master (currently ESP32-S2 specific):
+/// This module is a manual translation of a bunch of C files from current ESP-IDF master (ESP32-S2 and ESP32-S3):
 /// - https://github.com/espressif/esp-idf/blob/master/components/soc/esp32s2/include/soc/soc.h (a subset)
 /// - https://github.com/espressif/esp-idf/blob/master/components/soc/esp32s2/include/soc/sens_reg.h (a subset)
 /// - https://github.com/espressif/esp-idf/blob/master/components/soc/esp32s2/include/soc/rtc_io_reg.h (a subset)
+/// - https://github.com/espressif/esp-idf/blob/master/components/soc/esp32s2/include/soc/rtc_i2c_reg.h (a subset)
+/// - the same headers under https://github.com/espressif/esp-idf/blob/master/components/soc/esp32s3/include/soc
+///
+/// The RTC register blocks of both chips share the same layout, except for RTC_CNTL_COCPU_CTRL_REG
+/// and the SENS block: the ESP32-S3 has no SAR reader status registers, which shifts all SENS registers
+/// following them.
 use super::reg::bit;
 
+#[cfg(esp32s2)]
 pub const DR_REG_SENS_BASE: u32 = 0x3f408800;
-pub const DR_REG_RTCIO_BASE: u32 = 0x3ff48400;
+#[cfg(esp32s2)]
+pub const DR_REG_RTCIO_BASE: u32 = 0x3f408400;
+#[cfg(esp32s2)]
 pub const DR_REG_RTCCNTL_BASE: u32 = 0x3f408000;
+#[cfg(esp32s2)]
+pub const DR_REG_RTC_I2C_BASE: u32 = 0x3f408c00;
 
+#[cfg(esp32s3)]
+pub const DR_REG_SENS_BASE: u32 = 0x60008800;
+#[cfg(esp32s3)]
+pub const DR_REG_RTCIO_BASE: u32 = 0x60008400;
+#[cfg(esp32s3)]
+pub const DR_REG_RTCCNTL_BASE: u32 = 0x60008000;
+#[cfg(esp32s3)]
+pub const DR_REG_RTC_I2C_BASE: u32 = 0x60008c00;
+
+#[cfg(esp32s2)]
 pub const RTC_CNTL_COCPU_CTRL_REG: u32 = DR_REG_RTCCNTL_BASE + 0x0100;
+#[cfg(esp32s3)]
+pub const RTC_CNTL_COCPU_CTRL_REG: u32 = DR_REG_RTCCNTL_BASE + 0x0104;
+pub const RTC_CNTL_COCPU_SW_INT_TRIGGER: u32 = bit(26);
 pub const RTC_CNTL_COCPU_DONE: u32 = bit(25);
 pub const RTC_CNTL_COCPU_SHUT_RESET_EN: u32 = bit(22);
 pub const RTC_CNTL_COCPU_SHUT_2_CLK_DIS: u32 = 0x000000FF;
@@ -23,9 +47,105 @@ pub const RTC_CNTL_ULP_CP_SLP_TIMER_EN: u32 = bit(31);
 pub const RTC_CNTL_ULP_CP_SLP_TIMER_EN_V: u32 = 0x1;
 pub const RTC_CNTL_ULP_CP_SLP_TIMER_EN_S: u32 = 31;
 
+#[cfg(esp32s2)]
+pub const SENS_SAR_MEAS1_CTRL2_REG: u32 = DR_REG_SENS_BASE + 0x000c;
+#[cfg(esp32s3)]
+pub const SENS_SAR_MEAS1_CTRL2_REG: u32 = DR_REG_SENS_BASE + 0x0008;
+pub const SENS_MEAS1_DATA_SAR: u32 = 0x0000FFFF;
+pub const SENS_MEAS1_DATA_SAR_V: u32 = 0xFFFF;
+pub const SENS_MEAS1_DATA_SAR_S: u32 = 0;
+pub const SENS_MEAS1_DONE_SAR: u32 = bit(16);
+pub const SENS_MEAS1_START_SAR: u32 = bit(17);
+pub const SENS_MEAS1_START_FORCE: u32 = bit(18);
+pub const SENS_SAR1_EN_PAD: u32 = 0x00000FFF;
+pub const SENS_SAR1_EN_PAD_V: u32 = 0xFFF;
+pub const SENS_SAR1_EN_PAD_S: u32 = 19;
+pub const SENS_SAR1_EN_PAD_FORCE: u32 = bit(31);
+
+#[cfg(esp32s2)]
+pub const SENS_SAR_MEAS2_CTRL2_REG: u32 = DR_REG_SENS_BASE + 0x0030;
+#[cfg(esp32s3)]
+pub const SENS_SAR_MEAS2_CTRL2_REG: u32 = DR_REG_SENS_BASE + 0x0028;
+pub const SENS_MEAS2_DATA_SAR: u32 = 0x0000FFFF;
+pub const SENS_MEAS2_DATA_SAR_V: u32 = 0xFFFF;
+pub const SENS_MEAS2_DATA_SAR_S: u32 = 0;
+pub const SENS_MEAS2_DONE_SAR: u32 = bit(16);
+pub const SENS_MEAS2_START_SAR: u32 = bit(17);
+pub const SENS_MEAS2_START_FORCE: u32 = bit(18);
+pub const SENS_SAR2_EN_PAD: u32 = 0x00000FFF;
+pub const SENS_SAR2_EN_PAD_V: u32 = 0xFFF;
+pub const SENS_SAR2_EN_PAD_S: u32 = 19;
+pub const SENS_SAR2_EN_PAD_FORCE: u32 = bit(31);
+
+#[cfg(esp32s2)]
+pub const SENS_SAR_SLAVE_ADDR1_REG: u32 = DR_REG_SENS_BASE + 0x0040;
+#[cfg(esp32s3)]
+pub const SENS_SAR_SLAVE_ADDR1_REG: u32 = DR_REG_SENS_BASE + 0x0038;
+pub const SENS_I2C_SLAVE_ADDR0: u32 = 0x000007FF;
+pub const SENS_I2C_SLAVE_ADDR0_V: u32 = 0x7FF;
+pub const SENS_I2C_SLAVE_ADDR0_S: u32 = 11;
+pub const SENS_I2C_SLAVE_ADDR1: u32 = 0x000007FF;
+pub const SENS_I2C_SLAVE_ADDR1_V: u32 = 0x7FF;
+pub const SENS_I2C_SLAVE_ADDR1_S: u32 = 0;
+
+#[cfg(esp32s2)]
+pub const SENS_SAR_TSENS_CTRL_REG: u32 = DR_REG_SENS_BASE + 0x0050;
+#[cfg(esp32s3)]
+pub const SENS_SAR_TSENS_CTRL_REG: u32 = DR_REG_SENS_BASE + 0x0048;
+pub const SENS_TSENS_OUT: u32 = 0x000000FF;
+pub const SENS_TSENS_OUT_V: u32 = 0xFF;
+pub const SENS_TSENS_OUT_S: u32 = 0;
+pub const SENS_TSENS_READY: u32 = bit(8);
+pub const SENS_TSENS_POWER_UP: u32 = bit(22);
+pub const SENS_TSENS_POWER_UP_FORCE: u32 = bit(23);
+pub const SENS_TSENS_DUMP_OUT: u32 = bit(24);
+
+#[cfg(esp32s2)]
+pub const SENS_SAR_I2C_CTRL_REG: u32 = DR_REG_SENS_BASE + 0x0058;
+#[cfg(esp32s3)]
+pub const SENS_SAR_I2C_CTRL_REG: u32 = DR_REG_SENS_BASE + 0x0050;
+pub const SENS_SAR_I2C_CTRL: u32 = 0x0FFFFFFF;
+pub const SENS_SAR_I2C_CTRL_V: u32 = 0xFFFFFFF;
+pub const SENS_SAR_I2C_CTRL_S: u32 = 0;
+pub const SENS_SAR_I2C_START: u32 = bit(28);
+pub const SENS_SAR_I2C_START_FORCE: u32 = bit(29);
+
+#[cfg(esp32s2)]
+pub const SENS_SAR_COCPU_INT_ENA_REG: u32 = DR_REG_SENS_BASE + 0x00ec;
+#[cfg(esp32s3)]
+pub const SENS_SAR_COCPU_INT_ENA_REG: u32 = DR_REG_SENS_BASE + 0x00e4;
+pub const SENS_COCPU_SW_INT_ENA: u32 = bit(7);
+
+#[cfg(esp32s2)]
+pub const SENS_SAR_COCPU_INT_ST_REG: u32 = DR_REG_SENS_BASE + 0x00f0;
+#[cfg(esp32s3)]
+pub const SENS_SAR_COCPU_INT_ST_REG: u32 = DR_REG_SENS_BASE + 0x00e8;
+pub const SENS_COCPU_SW_INT_ST: u32 = bit(7);
+
+#[cfg(esp32s2)]
+pub const SENS_SAR_COCPU_INT_CLR_REG: u32 = DR_REG_SENS_BASE + 0x00f4;
+#[cfg(esp32s3)]
+pub const SENS_SAR_COCPU_INT_CLR_REG: u32 = DR_REG_SENS_BASE + 0x00ec;
+pub const SENS_COCPU_SW_INT_CLR: u32 = bit(7);
+
+#[cfg(esp32s2)]
+pub const SENS_SAR_TOUCH_STATUS1_REG: u32 = DR_REG_SENS_BASE + 0x00a4;
+#[cfg(esp32s3)]
+pub const SENS_SAR_TOUCH_STATUS1_REG: u32 = DR_REG_SENS_BASE + 0x009c;
+pub const SENS_TOUCH_PAD1_DATA: u32 = 0x003FFFFF;
+pub const SENS_TOUCH_PAD1_DATA_V: u32 = 0x3FFFFF;
+pub const SENS_TOUCH_PAD1_DATA_S: u32 = 0;
+
+#[cfg(esp32s2)]
 pub const SENS_SAR_IO_MUX_CONF_REG: u32 = DR_REG_SENS_BASE + 0x0144;
+#[cfg(esp32s2)]
 pub const SENS_IOMUX_CLK_GATE_EN_M: u32 = bit(31);
 
+#[cfg(esp32s3)]
+pub const SENS_SAR_PERI_CLK_GATE_CONF_REG: u32 = DR_REG_SENS_BASE + 0x0104;
+#[cfg(esp32s3)]
+pub const SENS_IOMUX_CLK_EN_M: u32 = bit(31);
+
 pub const RTC_IO_TOUCH_PAD0_REG: u32 = DR_REG_RTCIO_BASE + 0x84;
 pub const RTC_IO_TOUCH_PAD0_DRV: u32 = 0x00000003;
 pub const RTC_IO_TOUCH_PAD0_DRV_V: u32 = 0x3;
@@ -50,6 +170,21 @@ pub const RTC_GPIO_ENABLE_W1TC: u32 = 0x0003FFFF;
 pub const RTC_GPIO_ENABLE_W1TC_V: u32 = 0x3FFFF;
 pub const RTC_GPIO_ENABLE_W1TC_S: u32 = 10;
 
+pub const RTC_GPIO_STATUS_REG: u32 = DR_REG_RTCIO_BASE + 0x18;
+pub const RTC_GPIO_STATUS_INT: u32 = 0x003FFFFF;
+pub const RTC_GPIO_STATUS_INT_V: u32 = 0x3FFFFF;
+pub const RTC_GPIO_STATUS_INT_S: u32 = 10;
+
+pub const RTC_GPIO_STATUS_W1TC_REG: u32 = DR_REG_RTCIO_BASE + 0x20;
+pub const RTC_GPIO_STATUS_INT_W1TC: u32 = 0x003FFFFF;
+pub const RTC_GPIO_STATUS_INT_W1TC_V: u32 = 0x3FFFFF;
+pub const RTC_GPIO_STATUS_INT_W1TC_S: u32 = 10;
+
+pub const RTC_GPIO_PIN0_REG: u32 = DR_REG_RTCIO_BASE + 0x28;
+pub const RTC_GPIO_PIN0_INT_TYPE: u32 = 0x00000007;
+pub const RTC_GPIO_PIN0_INT_TYPE_V: u32 = 0x7;
+pub const RTC_GPIO_PIN0_INT_TYPE_S: u32 = 7;
+
 pub const RTC_GPIO_IN_REG: u32 = DR_REG_RTCIO_BASE + 0x24;
 pub const RTC_GPIO_IN_NEXT: u32 = 0x0003FFFF;
 pub const RTC_GPIO_IN_NEXT_V: u32 = 0x3FFFF;
@@ -64,3 +199,15 @@ pub const RTC_GPIO_OUT_W1TC_REG: u32 = DR_REG_RTCIO_BASE + 0x8;
 pub const RTC_GPIO_OUT_DATA_W1TC: u32 = 0x0003FFFF;
 pub const RTC_GPIO_OUT_DATA_W1TC_V: u32 = 0x3FFFF;
 pub const RTC_GPIO_OUT_DATA_W1TC_S: u32 = 10;
+
+pub const RTC_I2C_INT_CLR_REG: u32 = DR_REG_RTC_I2C_BASE + 0x0024;
+pub const RTC_I2C_INT_RAW_REG: u32 = DR_REG_RTC_I2C_BASE + 0x0028;
+pub const RTC_I2C_ARBITRATION_LOST_INT_RAW: u32 = bit(1);
+pub const RTC_I2C_TRANS_COMPLETE_INT_RAW: u32 = bit(3);
+pub const RTC_I2C_TIME_OUT_INT_RAW: u32 = bit(4);
+pub const RTC_I2C_ACK_ERR_INT_RAW: u32 = bit(5);
+
+pub const RTC_I2C_DATA_REG: u32 = DR_REG_RTC_I2C_BASE + 0x0034;
+pub const RTC_I2C_RDATA: u32 = 0x000000FF;
+pub const RTC_I2C_RDATA_V: u32 = 0xFF;
+pub const RTC_I2C_RDATA_S: u32 = 0;
@@ -4,6 +4,7 @@
 #![deny(missing_docs)]
 
 use super::sys::cpu;
+use super::sys::{ULP_RISCV_BUS_ERROR_INT, ULP_RISCV_EBREAK_ECALL_ILLEGAL_INSN_INT};
 
 #[export_name = "error: ulp_start appears more than once in the dependency graph"]
 #[doc(hidden)]
@@ -58,36 +59,22 @@ pub struct TrapFrame {
 /// Trap entry point rust (_start_trap_rust)
 /// This function is NOT supposed to be called from use code
 ///
-/// `mcause` is read to determine the cause of the trap. XLEN-1 bit indicates
-/// if it's an interrupt or an exception. The result is examined and ExceptionHandler
-/// or one of the core interrupt handlers is called.
+/// The ULP core has no `mcause` CSR; instead, the IRQ vector passes the pending IRQs
+/// from the custom `q1` register. Illegal instructions, `ebreak`/`ecall` and bus errors
+/// are reported as IRQs too and are routed to ExceptionHandler, while peripheral IRQs
+/// are dispatched to the handlers subscribed via the `interrupt` module.
 #[link_section = ".trap.rust"]
 #[export_name = "_start_trap_rust"]
-pub unsafe extern "C" fn start_trap_rust(trap_frame: *const TrapFrame) {
-    // use riscv::register::mcause;
-
+pub unsafe extern "C" fn start_trap_rust(trap_frame: *const TrapFrame, irq_status: u32) {
     extern "C" {
         fn ExceptionHandler(trap_frame: &TrapFrame);
-        #[allow(dead_code)]
-        fn DefaultHandler();
     }
 
-    // let cause = mcause::read();
-    // if cause.is_exception() {
-    ExceptionHandler(trap_frame.as_ref().unwrap())
-    // } else {
-    //     let code = cause.code();
-    //     if code < __INTERRUPTS.len() {
-    //         let h = &__INTERRUPTS[code];
-    //         if h.reserved == 0 {
-    //             DefaultHandler();
-    //         } else {
-    //             (h.handler)();
-    //         }
-    //     } else {
-    //         DefaultHandler();
-    //     }
-    // }
+    if irq_status & (ULP_RISCV_EBREAK_ECALL_ILLEGAL_INSN_INT | ULP_RISCV_BUS_ERROR_INT) != 0 {
+        ExceptionHandler(trap_frame.as_ref().unwrap());
+    }
+
+    super::interrupt::dispatch(irq_status);
 }
 
 #[doc(hidden)]
@@ -1,11 +1,26 @@
 /// A mini "esp-idf-ulp-sys" module exposing stuff on top of which the ULP HAL support is implemented
-/// (currently, only GPIO) + some utilities for the riscv ULP processor
+/// (GPIO, ADC, RTC I2C, touch, temperature sensor and interrupts) + some utilities for the riscv ULP processor
+pub use self::adc::*;
 pub use self::cpu::*;
 pub use self::gpio::*;
+pub use self::i2c::*;
+pub use self::interrupt::*;
+pub use self::touch::*;
+pub use self::tsens::*;
 
+#[allow(clippy::missing_safety_doc)]
+pub mod adc;
 pub mod cpu;
 #[allow(clippy::missing_safety_doc)]
 pub mod gpio;
+#[allow(clippy::missing_safety_doc)]
+pub mod i2c;
+#[allow(clippy::missing_safety_doc)]
+pub mod interrupt;
+#[allow(clippy::missing_safety_doc)]
+pub mod touch;
+#[allow(clippy::missing_safety_doc)]
+pub mod tsens;
 
 pub type EspError = core::convert::Infallible;
 
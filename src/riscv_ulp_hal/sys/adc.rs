@@ -0,0 +1,51 @@
+/// A manual translation of the RTC-controller oneshot path used by
+/// `ulp_riscv_adc_read_channel` in current ESP-IDF S2 master:
+/// - https://github.com/espressif/esp-idf/blob/master/components/ulp/ulp_riscv/ulp_core/ulp_riscv_adc.c
+/// - https://github.com/espressif/esp-idf/blob/master/components/hal/esp32s2/include/hal/adc_ll.h (`adc_ll_rtc_*`)
+///
+/// The SAR ADCs have to be handed over to the RTC controller by the main core
+/// (`ulp_adc_init` on ESP-IDF 5, `adc1_ulp_enable` on ESP-IDF 4.4) before any of these are called;
+/// attenuation and bit width are configured there as well.
+use crate::riscv_ulp_hal::pac::*;
+use crate::riscv_ulp_hal::reg::*;
+
+use super::gpio::{adc_channel_t, adc_unit_t, adc_unit_t_ADC_UNIT_1};
+
+#[inline(always)]
+pub unsafe fn ulp_riscv_adc_read_channel(adc_n: adc_unit_t, channel: adc_channel_t) -> i32 {
+    let (ctrl_reg, en_pad_s, en_pad_v, start, done, data_s, data_v) =
+        if adc_n == adc_unit_t_ADC_UNIT_1 {
+            (
+                SENS_SAR_MEAS1_CTRL2_REG,
+                SENS_SAR1_EN_PAD_S,
+                SENS_SAR1_EN_PAD_V,
+                SENS_MEAS1_START_SAR,
+                SENS_MEAS1_DONE_SAR,
+                SENS_MEAS1_DATA_SAR_S,
+                SENS_MEAS1_DATA_SAR_V,
+            )
+        } else {
+            (
+                SENS_SAR_MEAS2_CTRL2_REG,
+                SENS_SAR2_EN_PAD_S,
+                SENS_SAR2_EN_PAD_V,
+                SENS_MEAS2_START_SAR,
+                SENS_MEAS2_DONE_SAR,
+                SENS_MEAS2_DATA_SAR_S,
+                SENS_MEAS2_DATA_SAR_V,
+            )
+        };
+
+    reg_set_field(ctrl_reg, en_pad_s, en_pad_v, bit(channel as u32));
+
+    clear_peri_reg_mask(ctrl_reg, start);
+    set_peri_reg_mask(ctrl_reg, start);
+
+    while get_peri_reg_mask(ctrl_reg, done) == 0 {}
+
+    let raw = reg_get_field(ctrl_reg, data_s, data_v);
+
+    reg_set_field(ctrl_reg, en_pad_s, en_pad_v, 0);
+
+    raw as i32
+}
@@ -8,7 +8,10 @@ use core::arch::asm;
 use crate::riscv_ulp_hal::pac::*;
 use crate::riscv_ulp_hal::reg::*;
 
+#[cfg(esp32s2)]
 pub const ULP_RISCV_CYCLES_PER_US_NUM: u32 = 85;
+#[cfg(esp32s3)]
+pub const ULP_RISCV_CYCLES_PER_US_NUM: u32 = 175;
 pub const ULP_RISCV_CYCLES_PER_US_DENUM: u32 = 10;
 pub const ULP_RISCV_CYCLES_PER_MS: u32 =
     ULP_RISCV_CYCLES_PER_US_NUM * (1000 / ULP_RISCV_CYCLES_PER_US_DENUM);
@@ -1,6 +1,6 @@
 /// A mini "esp-idf-ulp-sys" module exposing stuff on top of which the ULP HAL support is implemented
 /// (currently, only GPIO)
-/// Implemented as a manual transation of a few C fields from current ESP-IDF S2 master:
+/// Implemented as a manual transation of a few C fields from current ESP-IDF S2 / S3 master:
 /// - https://github.com/espressif/esp-idf/blob/master/components/ulp/ulp_riscv/include/ulp_riscv/ulp_riscv_gpio.h
 use crate::riscv_ulp_hal::pac::*;
 use crate::riscv_ulp_hal::reg::*;
@@ -67,7 +67,10 @@ pub unsafe fn gpio_set_direction(gpio_num: i32, direction: u8) {
         return;
     } else {
         // Init
+        #[cfg(esp32s2)]
         set_peri_reg_mask(SENS_SAR_IO_MUX_CONF_REG, SENS_IOMUX_CLK_GATE_EN_M);
+        #[cfg(esp32s3)]
+        set_peri_reg_mask(SENS_SAR_PERI_CLK_GATE_CONF_REG, SENS_IOMUX_CLK_EN_M);
         set_peri_reg_mask(
             RTC_IO_TOUCH_PAD0_REG + gpio_num as u32 * 4,
             RTC_IO_TOUCH_PAD0_MUX_SEL,
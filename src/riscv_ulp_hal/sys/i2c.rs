@@ -0,0 +1,76 @@
+/// A manual translation of the SENS-triggered RTC I2C transfer used by the `I2C_RD` / `I2C_WR`
+/// ULP FSM instructions, as documented for the ESP32-S2 in the ESP-IDF S2 master sources:
+/// - https://github.com/espressif/esp-idf/blob/master/components/soc/esp32s2/include/soc/sens_reg.h
+/// - https://github.com/espressif/esp-idf/blob/master/components/soc/esp32s2/include/soc/rtc_i2c_reg.h
+///
+/// The RTC I2C controller (pins, timing) and the slave address table (`SENS_SAR_SLAVE_ADDRx_REG`)
+/// have to be configured by the main core before any of these are called.
+use crate::riscv_ulp_hal::pac::*;
+use crate::riscv_ulp_hal::reg::*;
+
+/// Number of entries in the slave address table
+pub const ULP_RISCV_I2C_SLAVE_ADDR_NUM: u32 = 8;
+
+pub const ULP_RISCV_I2C_ERR_ARBITRATION_LOST: u32 = RTC_I2C_ARBITRATION_LOST_INT_RAW;
+pub const ULP_RISCV_I2C_ERR_TIME_OUT: u32 = RTC_I2C_TIME_OUT_INT_RAW;
+pub const ULP_RISCV_I2C_ERR_ACK: u32 = RTC_I2C_ACK_ERR_INT_RAW;
+
+const ULP_RISCV_I2C_DONE_MASK: u32 = RTC_I2C_TRANS_COMPLETE_INT_RAW
+    | ULP_RISCV_I2C_ERR_ARBITRATION_LOST
+    | ULP_RISCV_I2C_ERR_TIME_OUT
+    | ULP_RISCV_I2C_ERR_ACK;
+
+/// Returns the 7-bit address stored in entry `slave_sel` of the slave address table
+#[inline(always)]
+pub unsafe fn ulp_riscv_i2c_get_slave_addr(slave_sel: u32) -> u16 {
+    let reg = SENS_SAR_SLAVE_ADDR1_REG + (slave_sel / 2) * 4;
+
+    if slave_sel % 2 == 0 {
+        reg_get_field(reg, SENS_I2C_SLAVE_ADDR0_S, SENS_I2C_SLAVE_ADDR0_V) as u16
+    } else {
+        reg_get_field(reg, SENS_I2C_SLAVE_ADDR1_S, SENS_I2C_SLAVE_ADDR1_V) as u16
+    }
+}
+
+/// Runs a single-byte register transfer with the slave in entry `slave_sel` of the
+/// slave address table and returns the error bits (`ULP_RISCV_I2C_ERR_*`) of the transfer
+/// (0 on success)
+#[inline(always)]
+pub unsafe fn ulp_riscv_i2c_transfer(slave_sel: u32, sub_addr: u8, data: u8, write: bool) -> u32 {
+    // Same layout as the lower 28 bits of the ULP FSM `I2C_RD` / `I2C_WR` instructions:
+    // sub address, data, bit range [7:0] of the register, slave select and direction
+    let ctrl = sub_addr as u32
+        | (data as u32) << 8
+        | 7 << 19
+        | (slave_sel & 0xf) << 22
+        | if write { 1 << 27 } else { 0 };
+
+    write_peri_reg(RTC_I2C_INT_CLR_REG, ULP_RISCV_I2C_DONE_MASK);
+
+    reg_set_field(
+        SENS_SAR_I2C_CTRL_REG,
+        SENS_SAR_I2C_CTRL_S,
+        SENS_SAR_I2C_CTRL_V,
+        ctrl,
+    );
+    set_peri_reg_mask(SENS_SAR_I2C_CTRL_REG, SENS_SAR_I2C_START_FORCE);
+    set_peri_reg_mask(SENS_SAR_I2C_CTRL_REG, SENS_SAR_I2C_START);
+
+    let status = loop {
+        let status = get_peri_reg_mask(RTC_I2C_INT_RAW_REG, ULP_RISCV_I2C_DONE_MASK);
+        if status != 0 {
+            break status;
+        }
+    };
+
+    clear_peri_reg_mask(SENS_SAR_I2C_CTRL_REG, SENS_SAR_I2C_START);
+    write_peri_reg(RTC_I2C_INT_CLR_REG, ULP_RISCV_I2C_DONE_MASK);
+
+    status & !RTC_I2C_TRANS_COMPLETE_INT_RAW
+}
+
+/// Returns the byte received by the last read transfer
+#[inline(always)]
+pub unsafe fn ulp_riscv_i2c_read_data() -> u8 {
+    reg_get_field(RTC_I2C_DATA_REG, RTC_I2C_RDATA_S, RTC_I2C_RDATA_V) as u8
+}
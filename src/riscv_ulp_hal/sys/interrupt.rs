@@ -0,0 +1,88 @@
+/// A manual translation of the following C files from current ESP-IDF master:
+/// - https://github.com/espressif/esp-idf/blob/master/components/ulp/ulp_riscv/ulp_core/include/ulp_riscv_interrupt_ops.h
+/// - https://github.com/espressif/esp-idf/blob/master/components/ulp/ulp_riscv/ulp_core/ulp_riscv_interrupt.c
+///
+/// The ULP RISC-V core is a PicoRV32, which has no `mcause`/`mie` CSRs: IRQs are masked and
+/// acknowledged with the custom `maskirq` / `retirq` instructions and the pending IRQs are
+/// delivered in the custom `q1` register (which the IRQ vector in `riscv_ulp_start.S` passes on).
+use core::arch::asm;
+
+use crate::riscv_ulp_hal::pac::*;
+use crate::riscv_ulp_hal::reg::*;
+
+pub const ULP_RISCV_TIMER_INT: u32 = bit(0);
+pub const ULP_RISCV_EBREAK_ECALL_ILLEGAL_INSN_INT: u32 = bit(1);
+pub const ULP_RISCV_BUS_ERROR_INT: u32 = bit(2);
+pub const ULP_RISCV_PERIPHERAL_INTERRUPT: u32 = bit(31);
+
+pub const ULP_RISCV_INTERNAL_INTERRUPT: u32 =
+    ULP_RISCV_TIMER_INT | ULP_RISCV_EBREAK_ECALL_ILLEGAL_INSN_INT | ULP_RISCV_BUS_ERROR_INT;
+pub const ULP_RISCV_INTR_MASK: u32 = ULP_RISCV_INTERNAL_INTERRUPT | ULP_RISCV_PERIPHERAL_INTERRUPT;
+
+pub const RTCIO_INTR_DISABLE: u32 = 0;
+pub const RTCIO_INTR_POSEDGE: u32 = 1;
+pub const RTCIO_INTR_NEGEDGE: u32 = 2;
+pub const RTCIO_INTR_ANYEDGE: u32 = 3;
+pub const RTCIO_INTR_LOW_LEVEL: u32 = 4;
+pub const RTCIO_INTR_HIGH_LEVEL: u32 = 5;
+
+/// Replaces the IRQ mask (a set bit masks the corresponding IRQ) and returns the previous one
+#[inline(always)]
+pub unsafe fn ulp_riscv_maskirq(mask: u32) -> u32 {
+    let old_mask;
+
+    // maskirq a0, a0
+    asm!(".word 0x0605650b", inlateout("a0") mask => old_mask);
+
+    old_mask
+}
+
+#[inline(always)]
+pub unsafe fn ulp_riscv_enable_sw_intr() {
+    set_peri_reg_mask(SENS_SAR_COCPU_INT_ENA_REG, SENS_COCPU_SW_INT_ENA);
+}
+
+#[inline(always)]
+pub unsafe fn ulp_riscv_disable_sw_intr() {
+    clear_peri_reg_mask(SENS_SAR_COCPU_INT_ENA_REG, SENS_COCPU_SW_INT_ENA);
+}
+
+/// Returns `true` and acknowledges the software interrupt raised by the main CPU, if pending
+#[inline(always)]
+pub unsafe fn ulp_riscv_take_sw_intr() -> bool {
+    if get_peri_reg_mask(SENS_SAR_COCPU_INT_ST_REG, SENS_COCPU_SW_INT_ST) != 0 {
+        set_peri_reg_mask(SENS_SAR_COCPU_INT_CLR_REG, SENS_COCPU_SW_INT_CLR);
+        true
+    } else {
+        false
+    }
+}
+
+#[inline(always)]
+pub unsafe fn ulp_riscv_gpio_set_intr_type(gpio_num: i32, intr_type: u32) {
+    reg_set_field(
+        RTC_GPIO_PIN0_REG + gpio_num as u32 * 4,
+        RTC_GPIO_PIN0_INT_TYPE_S,
+        RTC_GPIO_PIN0_INT_TYPE_V,
+        intr_type,
+    );
+}
+
+/// Returns and acknowledges the pending RTC IO interrupts, one bit per RTC GPIO
+#[inline(always)]
+pub unsafe fn ulp_riscv_gpio_take_intr_status() -> u32 {
+    let status = reg_get_field(
+        RTC_GPIO_STATUS_REG,
+        RTC_GPIO_STATUS_INT_S,
+        RTC_GPIO_STATUS_INT_V,
+    );
+
+    reg_set_field(
+        RTC_GPIO_STATUS_W1TC_REG,
+        RTC_GPIO_STATUS_INT_W1TC_S,
+        RTC_GPIO_STATUS_INT_W1TC_V,
+        status,
+    );
+
+    status
+}
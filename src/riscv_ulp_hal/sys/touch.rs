@@ -0,0 +1,22 @@
+/// A manual translation of a subset of the following C files from current ESP-IDF S2 master:
+/// - https://github.com/espressif/esp-idf/blob/master/components/ulp/ulp_riscv/ulp_core/include/ulp_riscv_touch_ulp_core.h
+/// - https://github.com/espressif/esp-idf/blob/master/components/hal/esp32s2/include/hal/touch_sensor_ll.h
+///
+/// The touch sensor FSM has to be configured and started by the main core
+/// (`touch_pad_init`, `touch_pad_config`, `touch_pad_fsm_start`).
+use crate::riscv_ulp_hal::pac::*;
+use crate::riscv_ulp_hal::reg::*;
+
+use super::gpio::touch_pad_t;
+
+pub const TOUCH_PAD_NUM1: touch_pad_t = 1;
+pub const TOUCH_PAD_MAX: touch_pad_t = 15;
+
+#[inline(always)]
+pub unsafe fn ulp_riscv_touch_pad_read_raw_data(touch_num: touch_pad_t) -> u32 {
+    reg_get_field(
+        SENS_SAR_TOUCH_STATUS1_REG + (touch_num - TOUCH_PAD_NUM1) as u32 * 4,
+        SENS_TOUCH_PAD1_DATA_S,
+        SENS_TOUCH_PAD1_DATA_V,
+    )
+}
@@ -0,0 +1,41 @@
+/// A manual translation of the temperature sensor oneshot path in current ESP-IDF S2 master:
+/// - https://github.com/espressif/esp-idf/blob/master/components/hal/esp32s2/include/hal/temperature_sensor_ll.h
+use crate::riscv_ulp_hal::pac::*;
+use crate::riscv_ulp_hal::reg::*;
+
+/// Offset of the default measurement range (-10 ~ 80 °C) selected by the main core driver
+pub const ULP_RISCV_TSENS_DAC_OFFSET: f32 = 0.0;
+
+#[inline(always)]
+pub unsafe fn ulp_riscv_tsens_power_up() {
+    set_peri_reg_mask(
+        SENS_SAR_TSENS_CTRL_REG,
+        SENS_TSENS_POWER_UP_FORCE | SENS_TSENS_POWER_UP,
+    );
+}
+
+#[inline(always)]
+pub unsafe fn ulp_riscv_tsens_power_down() {
+    clear_peri_reg_mask(
+        SENS_SAR_TSENS_CTRL_REG,
+        SENS_TSENS_POWER_UP_FORCE | SENS_TSENS_POWER_UP,
+    );
+}
+
+#[inline(always)]
+pub unsafe fn ulp_riscv_tsens_read_raw() -> u8 {
+    set_peri_reg_mask(SENS_SAR_TSENS_CTRL_REG, SENS_TSENS_DUMP_OUT);
+
+    while get_peri_reg_mask(SENS_SAR_TSENS_CTRL_REG, SENS_TSENS_READY) == 0 {}
+
+    let raw = reg_get_field(SENS_SAR_TSENS_CTRL_REG, SENS_TSENS_OUT_S, SENS_TSENS_OUT_V);
+
+    clear_peri_reg_mask(SENS_SAR_TSENS_CTRL_REG, SENS_TSENS_DUMP_OUT);
+
+    raw as u8
+}
+
+#[inline(always)]
+pub fn ulp_riscv_tsens_raw_to_celsius(raw: u8) -> f32 {
+    0.4386 * raw as f32 - 27.88 * ULP_RISCV_TSENS_DAC_OFFSET - 20.52
+}
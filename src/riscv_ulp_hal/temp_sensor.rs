@@ -0,0 +1,43 @@
+//! Internal temperature sensor support for the RiscV ULP coprocessor
+//!
+//! Readings are converted with the default measurement range (-10 ~ 80 °C) and
+//! without the factory calibration offset, which is only accessible from the main core.
+
+use crate::peripheral::{Peripheral, PeripheralRef};
+
+use super::sys::*;
+
+pub struct TempSensor<'d> {
+    _temp_sensor: PeripheralRef<'d, TEMP_SENSOR>,
+}
+
+impl<'d> TempSensor<'d> {
+    /// Powers up the temperature sensor
+    pub fn new(temp_sensor: impl Peripheral<P = TEMP_SENSOR> + 'd) -> Self {
+        crate::into_ref!(temp_sensor);
+
+        unsafe { ulp_riscv_tsens_power_up() };
+
+        Self {
+            _temp_sensor: temp_sensor,
+        }
+    }
+
+    /// Returns the raw 8-bit reading of the sensor
+    pub fn read_raw(&mut self) -> u8 {
+        unsafe { ulp_riscv_tsens_read_raw() }
+    }
+
+    /// Returns the temperature in °C
+    pub fn read_celsius(&mut self) -> f32 {
+        ulp_riscv_tsens_raw_to_celsius(self.read_raw())
+    }
+}
+
+impl<'d> Drop for TempSensor<'d> {
+    fn drop(&mut self) {
+        unsafe { ulp_riscv_tsens_power_down() };
+    }
+}
+
+crate::impl_peripheral!(TEMP_SENSOR);
@@ -0,0 +1,26 @@
+//! Touch sensor support for the RiscV ULP coprocessor
+//!
+//! The touch sensor FSM is configured and started by the main core; the ULP
+//! only reads the latest measurements of the touch channels.
+
+use crate::gpio::TouchPin;
+use crate::peripheral::{Peripheral, PeripheralRef};
+
+use super::sys::*;
+
+pub struct TouchDriver<'d, T: TouchPin> {
+    pin: PeripheralRef<'d, T>,
+}
+
+impl<'d, T: TouchPin> TouchDriver<'d, T> {
+    pub fn new(pin: impl Peripheral<P = T> + 'd) -> Self {
+        crate::into_ref!(pin);
+
+        Self { pin }
+    }
+
+    /// Returns the raw value of the last measurement of the touch channel
+    pub fn read_raw(&self) -> u32 {
+        unsafe { ulp_riscv_touch_pad_read_raw_data(self.pin.touch_channel()) }
+    }
+}
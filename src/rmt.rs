@@ -196,6 +196,7 @@ pub mod config {
     use esp_idf_sys::{EspError, ESP_ERR_INVALID_ARG};
 
     use super::PinState;
+    use crate::interrupt::InterruptConfig;
     use crate::units::{FromValueType, Hertz};
 
     /// A percentage from 0 to 100%, used to specify the duty percentage in [`CarrierConfig`].
@@ -283,6 +284,8 @@ pub mod config {
         /// When set, RMT channel will take REF_TICK or XTAL as source clock. The benefit is, RMT
         /// channel can continue work even when APB clock is changing.
         pub aware_dfs: bool,
+
+        pub interrupt: InterruptConfig,
     }
 
     impl TransmitConfig {
@@ -294,6 +297,7 @@ pub mod config {
                 looping: Loop::None,
                 carrier: None,
                 idle: Some(PinState::Low),
+                interrupt: Default::default(),
             }
         }
 
@@ -326,6 +330,11 @@ pub mod config {
             self.idle = idle;
             self
         }
+
+        pub fn interrupt(mut self, interrupt: InterruptConfig) -> Self {
+            self.interrupt = interrupt;
+            self
+        }
     }
 
     impl Default for TransmitConfig {
@@ -393,7 +402,7 @@ impl<'d, C: RmtChannel> RmtDriver<'d, C> {
 
         unsafe {
             esp!(rmt_config(&sys_config))?;
-            esp!(rmt_driver_install(C::channel(), 0, 0))?;
+            esp!(rmt_driver_install(C::channel(), 0, config.interrupt.into()))?;
         }
 
         Ok(Self { _channel: channel })
@@ -0,0 +1,213 @@
+//! Hardware random number generator
+//!
+//! Every ESP chip has a hardware RNG, which is what `esp_random()` reads. Its output is only
+//! truly random while an entropy source is active:
+//! - the RF subsystem, i.e. Wi-Fi or Bluetooth is started, or
+//! - the SAR ADC noise source, enabled with `EntropySource::enable` (the same source the
+//!   bootloader uses)
+//!
+//! Without either, the RNG output is pseudo-random and must not be used for cryptographic
+//! purposes. `Rng::new` therefore refuses to create an RNG unless an entropy source is active,
+//! and `Rng::new_unchecked` makes the caller responsible for it.
+//!
+//! `Rng` implements `rand_core::CryptoRng` under the contract that the entropy source stays
+//! active for as long as its output is used for cryptographic purposes - i.e. Wi-Fi or
+//! Bluetooth is not stopped, or the `EntropySource` is not dropped in the meantime.
+//! `EntropyRng` enforces this for the SAR ADC noise source, by keeping the `EntropySource`
+//! borrowed for as long as it lives.
+
+use core::marker::PhantomData;
+
+use esp_idf_sys::*;
+
+static ENTROPY_SOURCE_CS: crate::task::CriticalSection = crate::task::CriticalSection::new();
+static mut ENTROPY_SOURCE_REFS: u32 = 0;
+
+/// Returns `true` if the RNG currently has a true entropy source
+pub fn is_entropy_source_active() -> bool {
+    is_adc_entropy_enabled() || is_wifi_started() || is_bt_enabled()
+}
+
+fn is_adc_entropy_enabled() -> bool {
+    let _guard = ENTROPY_SOURCE_CS.enter();
+
+    unsafe { ENTROPY_SOURCE_REFS > 0 }
+}
+
+#[cfg(esp_idf_comp_esp_wifi_enabled)]
+fn is_wifi_started() -> bool {
+    let mut power = 0;
+
+    // Fails with `ESP_ERR_WIFI_NOT_INIT` / `ESP_ERR_WIFI_NOT_STARTED` until `esp_wifi_start`
+    unsafe { esp_wifi_get_max_tx_power(&mut power) == ESP_OK }
+}
+
+#[cfg(not(esp_idf_comp_esp_wifi_enabled))]
+fn is_wifi_started() -> bool {
+    false
+}
+
+#[cfg(esp_idf_bt_enabled)]
+fn is_bt_enabled() -> bool {
+    unsafe {
+        esp_bt_controller_get_status()
+            == esp_bt_controller_status_t_ESP_BT_CONTROLLER_STATUS_ENABLED
+    }
+}
+
+#[cfg(not(esp_idf_bt_enabled))]
+fn is_bt_enabled() -> bool {
+    false
+}
+
+/// Keeps the SAR ADC noise entropy source enabled while alive
+///
+/// The noise source takes over the SAR ADC (and on the ESP32 also the I2S0 peripheral),
+/// so neither the ADC drivers nor Wi-Fi / Bluetooth may be used while it is enabled.
+/// Once Wi-Fi or Bluetooth is started, the RF subsystem provides the entropy instead.
+pub struct EntropySource(PhantomData<*const ()>);
+
+impl EntropySource {
+    pub fn enable() -> Self {
+        let _guard = ENTROPY_SOURCE_CS.enter();
+
+        unsafe {
+            if ENTROPY_SOURCE_REFS == 0 {
+                bootloader_random_enable();
+            }
+
+            ENTROPY_SOURCE_REFS += 1;
+        }
+
+        Self(PhantomData)
+    }
+}
+
+impl Drop for EntropySource {
+    fn drop(&mut self) {
+        let _guard = ENTROPY_SOURCE_CS.enter();
+
+        unsafe {
+            ENTROPY_SOURCE_REFS -= 1;
+
+            if ENTROPY_SOURCE_REFS == 0 {
+                bootloader_random_disable();
+            }
+        }
+    }
+}
+
+unsafe impl Send for EntropySource {}
+
+/// The hardware RNG
+#[derive(Debug, Copy, Clone)]
+pub struct Rng(());
+
+impl Rng {
+    /// Returns `ESP_ERR_INVALID_STATE` if no entropy source is active
+    /// (see `is_entropy_source_active`).
+    ///
+    /// Note that the check is only done here: the RNG is still usable if the entropy source
+    /// is disabled later on, but its output is then no longer truly random, and must not be
+    /// used for cryptographic purposes anymore.
+    pub fn new() -> Result<Self, EspError> {
+        if is_entropy_source_active() {
+            Ok(Self(()))
+        } else {
+            Err(EspError::from(ESP_ERR_INVALID_STATE).unwrap())
+        }
+    }
+
+    /// # Safety
+    ///
+    /// An entropy source must be active (Wi-Fi or Bluetooth started, or an `EntropySource` alive)
+    /// whenever the output of the RNG is used for cryptographic purposes, as the output is only
+    /// pseudo-random otherwise.
+    pub unsafe fn new_unchecked() -> Self {
+        Self(())
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        unsafe { esp_random() }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        (self.next_u32() as u64) << 32 | self.next_u32() as u64
+    }
+
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        unsafe { esp_fill_random(dest.as_mut_ptr() as *mut _, dest.len() as _) }
+    }
+}
+
+impl rand_core::RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        Rng::next_u32(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        Rng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        Rng::fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        Rng::fill_bytes(self, dest);
+
+        Ok(())
+    }
+}
+
+/// Relies on the entropy source checked by `Rng::new` staying active (see the module docs)
+impl rand_core::CryptoRng for Rng {}
+
+/// The hardware RNG, with the SAR ADC noise entropy source kept enabled while alive
+pub struct EntropyRng<'a> {
+    rng: Rng,
+    _source: &'a EntropySource,
+}
+
+impl<'a> EntropyRng<'a> {
+    pub fn new(source: &'a EntropySource) -> Self {
+        Self {
+            rng: Rng(()),
+            _source: source,
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+}
+
+impl<'a> rand_core::RngCore for EntropyRng<'a> {
+    fn next_u32(&mut self) -> u32 {
+        EntropyRng::next_u32(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        EntropyRng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        EntropyRng::fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        EntropyRng::fill_bytes(self, dest);
+
+        Ok(())
+    }
+}
+
+impl<'a> rand_core::CryptoRng for EntropyRng<'a> {}
@@ -0,0 +1,911 @@
+//! SD / MMC card support
+//!
+//! Cards are accessed through one of two hosts:
+//! - `SdmmcHostDriver`, the SD/MMC host controller of the ESP32 and the ESP32-S3,
+//!   in 1-bit, 4-bit or 8-bit mode
+//! - `SdSpiHostDriver`, which talks to the card in SPI mode over the bus of a `SpiMasterDriver`
+//!
+//! `SdCardDriver` initializes the card behind either host and implements the `BlockDevice`
+//! trait. With the `std` feature enabled, `BlockDeviceStream` turns any `BlockDevice`
+//! into a `std::io::Read + Write + Seek` stream, as expected by Rust FAT filesystem crates.
+//!
+//! # Examples
+//!
+//! ```
+//! use esp_idf_hal::peripherals::Peripherals;
+//! use esp_idf_hal::sdmmc::{config, BlockDevice, SdCardDriver, SdmmcHostDriver};
+//!
+//! let peripherals = Peripherals::take().unwrap();
+//! let pins = peripherals.pins;
+//!
+//! let host = SdmmcHostDriver::new_4bit(
+//!     peripherals.sdmmc,
+//!     pins.gpio14,
+//!     pins.gpio15,
+//!     pins.gpio2,
+//!     pins.gpio4,
+//!     pins.gpio12,
+//!     pins.gpio13,
+//!     &config::Config::new(),
+//! )?;
+//!
+//! let mut card = SdCardDriver::new(host)?;
+//!
+//! let mut sector = [0_u8; 512];
+//! card.read_blocks(0, &mut sector)?;
+//! ```
+
+use esp_idf_sys::*;
+
+#[cfg(esp32)]
+use crate::gpio::Pin;
+use crate::gpio::{self, InputPin, OutputPin};
+use crate::peripheral::Peripheral;
+#[cfg(any(esp32, esp32s3))]
+use crate::peripheral::PeripheralRef;
+use crate::spi::{Spi, SpiMasterDriver};
+
+/// A device made of fixed-size blocks, such as an SD card
+pub trait BlockDevice {
+    type Error;
+
+    /// Size of a block in bytes
+    fn block_size(&self) -> usize;
+
+    /// Number of blocks of the device
+    fn block_count(&self) -> u64;
+
+    /// Reads `buf.len() / block_size()` consecutive blocks, starting at block `start`;
+    /// `buf.len()` must be a multiple of the block size
+    fn read_blocks(&mut self, start: u64, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Writes `buf.len() / block_size()` consecutive blocks, starting at block `start`;
+    /// `buf.len()` must be a multiple of the block size
+    fn write_blocks(&mut self, start: u64, buf: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl<T: BlockDevice> BlockDevice for &mut T {
+    type Error = T::Error;
+
+    fn block_size(&self) -> usize {
+        (**self).block_size()
+    }
+
+    fn block_count(&self) -> u64 {
+        (**self).block_count()
+    }
+
+    fn read_blocks(&mut self, start: u64, buf: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).read_blocks(start, buf)
+    }
+
+    fn write_blocks(&mut self, start: u64, buf: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_blocks(start, buf)
+    }
+}
+
+pub mod config {
+    use crate::units::*;
+
+    /// SD/MMC host configuration
+    #[derive(Copy, Clone, Debug)]
+    pub struct Config {
+        pub frequency: Hertz,
+        pub ddr: bool,
+        pub internal_pullups: bool,
+    }
+
+    impl Config {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        /// Maximum clock frequency, 20MHz (default speed) by default;
+        /// 40MHz selects high speed mode if the card supports it
+        #[must_use]
+        pub fn frequency(mut self, frequency: Hertz) -> Self {
+            self.frequency = frequency;
+            self
+        }
+
+        /// Use DDR mode if the card supports it (SDMMC host only)
+        #[must_use]
+        pub fn ddr(mut self, ddr: bool) -> Self {
+            self.ddr = ddr;
+            self
+        }
+
+        /// Enable the internal pullups of the CMD and data lines (SDMMC host only)
+        ///
+        /// These are weak and no replacement for the external 10K pullups required by the SD specification.
+        #[must_use]
+        pub fn internal_pullups(mut self, internal_pullups: bool) -> Self {
+            self.internal_pullups = internal_pullups;
+            self
+        }
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                frequency: Hertz(esp_idf_sys::SDMMC_FREQ_DEFAULT * 1000),
+                ddr: false,
+                internal_pullups: false,
+            }
+        }
+    }
+}
+
+/// A host the SD card is accessed through
+pub trait SdHost {
+    /// The ESP-IDF host descriptor, which `sdmmc_card_init` and the sector read/write
+    /// functions dispatch to
+    fn host(&self) -> sdmmc_host_t;
+}
+
+/// SD/MMC host controller driver
+///
+/// On the ESP32 the host only works with the pins of its IO MUX (slot 1 for 1-bit and 4-bit
+/// mode, slot 0 - which is shared with the SPI flash on most modules - for 8-bit mode);
+/// on the ESP32-S3 any pins can be used.
+#[cfg(any(esp32, esp32s3))]
+pub struct SdmmcHostDriver<'d> {
+    _sdmmc: PeripheralRef<'d, SDMMC>,
+    slot: i32,
+    width: u8,
+    max_freq_khz: i32,
+    ddr: bool,
+}
+
+#[cfg(esp32)]
+impl<'d> SdmmcHostDriver<'d> {
+    pub fn new_1bit(
+        sdmmc: impl Peripheral<P = SDMMC> + 'd,
+        clk: impl Peripheral<P = gpio::Gpio14> + 'd,
+        cmd: impl Peripheral<P = gpio::Gpio15> + 'd,
+        d0: impl Peripheral<P = gpio::Gpio2> + 'd,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(clk, cmd, d0);
+
+        Self::new_internal(
+            sdmmc,
+            SDMMC_HOST_SLOT_1 as _,
+            &[clk.pin(), cmd.pin(), d0.pin()],
+            config,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_4bit(
+        sdmmc: impl Peripheral<P = SDMMC> + 'd,
+        clk: impl Peripheral<P = gpio::Gpio14> + 'd,
+        cmd: impl Peripheral<P = gpio::Gpio15> + 'd,
+        d0: impl Peripheral<P = gpio::Gpio2> + 'd,
+        d1: impl Peripheral<P = gpio::Gpio4> + 'd,
+        d2: impl Peripheral<P = gpio::Gpio12> + 'd,
+        d3: impl Peripheral<P = gpio::Gpio13> + 'd,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(clk, cmd, d0, d1, d2, d3);
+
+        Self::new_internal(
+            sdmmc,
+            SDMMC_HOST_SLOT_1 as _,
+            &[clk.pin(), cmd.pin(), d0.pin(), d1.pin(), d2.pin(), d3.pin()],
+            config,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_8bit(
+        sdmmc: impl Peripheral<P = SDMMC> + 'd,
+        clk: impl Peripheral<P = gpio::Gpio6> + 'd,
+        cmd: impl Peripheral<P = gpio::Gpio11> + 'd,
+        d0: impl Peripheral<P = gpio::Gpio7> + 'd,
+        d1: impl Peripheral<P = gpio::Gpio8> + 'd,
+        d2: impl Peripheral<P = gpio::Gpio9> + 'd,
+        d3: impl Peripheral<P = gpio::Gpio10> + 'd,
+        d4: impl Peripheral<P = gpio::Gpio16> + 'd,
+        d5: impl Peripheral<P = gpio::Gpio17> + 'd,
+        d6: impl Peripheral<P = gpio::Gpio5> + 'd,
+        d7: impl Peripheral<P = gpio::Gpio18> + 'd,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(clk, cmd, d0, d1, d2, d3, d4, d5, d6, d7);
+
+        Self::new_internal(
+            sdmmc,
+            SDMMC_HOST_SLOT_0 as _,
+            &[
+                clk.pin(),
+                cmd.pin(),
+                d0.pin(),
+                d1.pin(),
+                d2.pin(),
+                d3.pin(),
+                d4.pin(),
+                d5.pin(),
+                d6.pin(),
+                d7.pin(),
+            ],
+            config,
+        )
+    }
+}
+
+#[cfg(esp32s3)]
+impl<'d> SdmmcHostDriver<'d> {
+    pub fn new_1bit(
+        sdmmc: impl Peripheral<P = SDMMC> + 'd,
+        clk: impl Peripheral<P = impl OutputPin> + 'd,
+        cmd: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d0: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(clk, cmd, d0);
+
+        Self::new_internal(
+            sdmmc,
+            SDMMC_HOST_SLOT_1 as _,
+            &[clk.pin(), cmd.pin(), d0.pin()],
+            config,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_4bit(
+        sdmmc: impl Peripheral<P = SDMMC> + 'd,
+        clk: impl Peripheral<P = impl OutputPin> + 'd,
+        cmd: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d0: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d1: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d2: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d3: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(clk, cmd, d0, d1, d2, d3);
+
+        Self::new_internal(
+            sdmmc,
+            SDMMC_HOST_SLOT_1 as _,
+            &[clk.pin(), cmd.pin(), d0.pin(), d1.pin(), d2.pin(), d3.pin()],
+            config,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_8bit(
+        sdmmc: impl Peripheral<P = SDMMC> + 'd,
+        clk: impl Peripheral<P = impl OutputPin> + 'd,
+        cmd: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d0: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d1: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d2: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d3: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d4: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d5: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d6: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        d7: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(clk, cmd, d0, d1, d2, d3, d4, d5, d6, d7);
+
+        Self::new_internal(
+            sdmmc,
+            SDMMC_HOST_SLOT_0 as _,
+            &[
+                clk.pin(),
+                cmd.pin(),
+                d0.pin(),
+                d1.pin(),
+                d2.pin(),
+                d3.pin(),
+                d4.pin(),
+                d5.pin(),
+                d6.pin(),
+                d7.pin(),
+            ],
+            config,
+        )
+    }
+}
+
+#[cfg(any(esp32, esp32s3))]
+impl<'d> SdmmcHostDriver<'d> {
+    /// `pins` are CLK, CMD and D0..D(width - 1)
+    fn new_internal(
+        sdmmc: impl Peripheral<P = SDMMC> + 'd,
+        slot: i32,
+        pins: &[i32],
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(sdmmc);
+
+        let width = (pins.len() - 2) as u8;
+
+        let mut slot_config = sdmmc_slot_config_t {
+            width,
+            flags: if config.internal_pullups {
+                SDMMC_SLOT_FLAG_INTERNAL_PULLUP
+            } else {
+                0
+            },
+            ..Default::default()
+        };
+
+        #[cfg(esp_idf_version_major = "4")]
+        {
+            slot_config.gpio_cd = gpio_num_t_GPIO_NUM_NC;
+            slot_config.gpio_wp = gpio_num_t_GPIO_NUM_NC;
+        }
+
+        #[cfg(not(esp_idf_version_major = "4"))]
+        {
+            slot_config.__bindgen_anon_1.gpio_cd = gpio_num_t_GPIO_NUM_NC;
+            slot_config.__bindgen_anon_2.gpio_wp = gpio_num_t_GPIO_NUM_NC;
+        }
+
+        // Only the ESP32-S3 routes the host signals through the GPIO matrix
+        #[cfg(esp32s3)]
+        {
+            let mut data = [gpio_num_t_GPIO_NUM_NC; 8];
+            data[..width as usize].copy_from_slice(&pins[2..]);
+
+            slot_config.clk = pins[0];
+            slot_config.cmd = pins[1];
+            slot_config.d0 = data[0];
+            slot_config.d1 = data[1];
+            slot_config.d2 = data[2];
+            slot_config.d3 = data[3];
+            slot_config.d4 = data[4];
+            slot_config.d5 = data[5];
+            slot_config.d6 = data[6];
+            slot_config.d7 = data[7];
+        }
+
+        esp!(unsafe { sdmmc_host_init() })?;
+
+        if let Err(err) = esp!(unsafe { sdmmc_host_init_slot(slot, &slot_config) }) {
+            esp!(unsafe { sdmmc_host_deinit() }).unwrap();
+
+            return Err(err);
+        }
+
+        Ok(Self {
+            _sdmmc: sdmmc,
+            slot,
+            width,
+            max_freq_khz: (config.frequency.0 / 1000) as _,
+            ddr: config.ddr,
+        })
+    }
+
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+}
+
+#[cfg(any(esp32, esp32s3))]
+impl<'d> SdHost for SdmmcHostDriver<'d> {
+    fn host(&self) -> sdmmc_host_t {
+        let width_flag = match self.width {
+            1 => SDMMC_HOST_FLAG_1BIT,
+            4 => SDMMC_HOST_FLAG_4BIT | SDMMC_HOST_FLAG_1BIT,
+            _ => SDMMC_HOST_FLAG_8BIT | SDMMC_HOST_FLAG_4BIT | SDMMC_HOST_FLAG_1BIT,
+        };
+
+        // Mirrors `SDMMC_HOST_DEFAULT()`
+        sdmmc_host_t {
+            flags: width_flag | if self.ddr { SDMMC_HOST_FLAG_DDR } else { 0 },
+            slot: self.slot,
+            max_freq_khz: self.max_freq_khz,
+            io_voltage: 3.3,
+            init: Some(sdmmc_host_init),
+            set_bus_width: Some(sdmmc_host_set_bus_width),
+            get_bus_width: Some(sdmmc_host_get_slot_width),
+            set_bus_ddr_mode: Some(sdmmc_host_set_bus_ddr_mode),
+            set_card_clk: Some(sdmmc_host_set_card_clk),
+            do_transaction: Some(sdmmc_host_do_transaction),
+            __bindgen_anon_1: sdmmc_host_t__bindgen_ty_1 {
+                deinit: Some(sdmmc_host_deinit),
+            },
+            io_int_enable: Some(sdmmc_host_io_int_enable),
+            io_int_wait: Some(sdmmc_host_io_int_wait),
+            command_timeout_ms: 0,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(any(esp32, esp32s3))]
+impl<'d> Drop for SdmmcHostDriver<'d> {
+    fn drop(&mut self) {
+        esp!(unsafe { sdmmc_host_deinit() }).unwrap();
+    }
+}
+
+/// The SD SPI host is shared by all `SdSpiHostDriver` instances, and only deinitialized with the last one
+static SDSPI_HOST_CS: crate::task::CriticalSection = crate::task::CriticalSection::new();
+static mut SDSPI_HOST_REFS: u32 = 0;
+
+fn sdspi_host_acquire() -> Result<(), EspError> {
+    let _guard = SDSPI_HOST_CS.enter();
+
+    unsafe {
+        if SDSPI_HOST_REFS == 0 {
+            esp!(sdspi_host_init())?;
+        }
+
+        SDSPI_HOST_REFS += 1;
+    }
+
+    Ok(())
+}
+
+fn sdspi_host_release() -> Result<(), EspError> {
+    let _guard = SDSPI_HOST_CS.enter();
+
+    unsafe {
+        SDSPI_HOST_REFS -= 1;
+
+        if SDSPI_HOST_REFS == 0 {
+            esp!(sdspi_host_deinit())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// SD card host on top of the bus of a `SpiMasterDriver`
+///
+/// The card is added as another device to the bus, with its own CS pin. The `SpiMasterDriver`
+/// must have been created with DMA enabled, as blocks are transferred in one go.
+pub struct SdSpiHostDriver<'s, 'd, SPI: Spi> {
+    _spi: &'s SpiMasterDriver<'d, SPI>,
+    handle: sdspi_dev_handle_t,
+    max_freq_khz: i32,
+}
+
+impl<'s, 'd, SPI: Spi> SdSpiHostDriver<'s, 'd, SPI> {
+    pub fn new(
+        spi: &'s SpiMasterDriver<'d, SPI>,
+        cs: impl Peripheral<P = impl OutputPin> + 's,
+        cd: Option<impl Peripheral<P = impl InputPin> + 's>,
+        wp: Option<impl Peripheral<P = impl InputPin> + 's>,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(cs);
+
+        let cd = cd.map(|cd| cd.into_ref());
+        let wp = wp.map(|wp| wp.into_ref());
+
+        let device_config = sdspi_device_config_t {
+            host_id: SPI::device(),
+            gpio_cs: cs.pin(),
+            gpio_cd: cd.as_ref().map_or(gpio_num_t_GPIO_NUM_NC, |p| p.pin()),
+            gpio_wp: wp.as_ref().map_or(gpio_num_t_GPIO_NUM_NC, |p| p.pin()),
+            gpio_int: gpio_num_t_GPIO_NUM_NC,
+            ..Default::default()
+        };
+
+        let mut handle: sdspi_dev_handle_t = 0;
+
+        sdspi_host_acquire()?;
+
+        if let Err(err) = esp!(unsafe { sdspi_host_init_device(&device_config, &mut handle) }) {
+            // Report the failure to add the device, not a failure to deinit the host
+            let _ = sdspi_host_release();
+
+            return Err(err);
+        }
+
+        Ok(Self {
+            _spi: spi,
+            handle,
+            max_freq_khz: (config.frequency.0 / 1000) as _,
+        })
+    }
+}
+
+impl<'s, 'd, SPI: Spi> SdHost for SdSpiHostDriver<'s, 'd, SPI> {
+    fn host(&self) -> sdmmc_host_t {
+        // Mirrors `SDSPI_HOST_DEFAULT()`
+        sdmmc_host_t {
+            flags: SDMMC_HOST_FLAG_SPI | SDMMC_HOST_FLAG_DEINIT_ARG,
+            slot: self.handle,
+            max_freq_khz: self.max_freq_khz,
+            io_voltage: 3.3,
+            init: Some(sdspi_host_init),
+            set_bus_width: None,
+            get_bus_width: None,
+            set_bus_ddr_mode: None,
+            set_card_clk: Some(sdspi_host_set_card_clk),
+            do_transaction: Some(sdspi_host_do_transaction),
+            __bindgen_anon_1: sdmmc_host_t__bindgen_ty_1 {
+                deinit_p: Some(sdspi_host_remove_device),
+            },
+            io_int_enable: Some(sdspi_host_io_int_enable),
+            io_int_wait: Some(sdspi_host_io_int_wait),
+            command_timeout_ms: 0,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'s, 'd, SPI: Spi> Drop for SdSpiHostDriver<'s, 'd, SPI> {
+    fn drop(&mut self) {
+        esp!(unsafe { sdspi_host_remove_device(self.handle) }).unwrap();
+
+        sdspi_host_release().unwrap();
+    }
+}
+
+/// The card identification register
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cid {
+    pub manufacturer_id: u8,
+    pub oem_id: u16,
+    pub name: heapless::String<8>,
+    pub revision: u8,
+    pub serial: u32,
+    /// Manufacturing date, as `(year - 2000) * 12 + month`
+    pub date: u16,
+}
+
+/// The card specific data register
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Csd {
+    pub version: u8,
+    pub mmc_version: u8,
+    /// Capacity in sectors
+    pub capacity: u32,
+    pub sector_size: u32,
+    pub read_block_len: u32,
+    pub card_command_class: u32,
+    pub transfer_speed: u32,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CardType {
+    Sd,
+    Mmc,
+    Sdio,
+}
+
+/// Information about an initialized card
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CardInfo {
+    pub card_type: CardType,
+    pub ocr: u32,
+    pub max_frequency_khz: u32,
+    pub cid: Cid,
+    pub csd: Csd,
+}
+
+impl CardInfo {
+    /// Capacity of the card in bytes
+    pub fn capacity(&self) -> u64 {
+        self.csd.capacity as u64 * self.csd.sector_size as u64
+    }
+}
+
+/// An SD / MMC memory card behind an `SdHost`
+pub struct SdCardDriver<H: SdHost> {
+    host: H,
+    card: sdmmc_card_t,
+}
+
+impl<H: SdHost> SdCardDriver<H> {
+    /// Probes and initializes the card; returns `ESP_ERR_TIMEOUT` / `ESP_ERR_NOT_FOUND`
+    /// if no card responds
+    pub fn new(host: H) -> Result<Self, EspError> {
+        let sys_host = host.host();
+        let mut card: sdmmc_card_t = Default::default();
+
+        esp!(unsafe { sdmmc_card_init(&sys_host, &mut card) })?;
+
+        Ok(Self { host, card })
+    }
+
+    pub fn host(&self) -> &H {
+        &self.host
+    }
+
+    pub fn info(&self) -> CardInfo {
+        let card_type = if self.card.is_sdio() != 0 {
+            CardType::Sdio
+        } else if self.card.is_mmc() != 0 {
+            CardType::Mmc
+        } else {
+            CardType::Sd
+        };
+
+        CardInfo {
+            card_type,
+            ocr: self.card.ocr,
+            max_frequency_khz: self.card.max_freq_khz as _,
+            cid: self.cid(),
+            csd: self.csd(),
+        }
+    }
+
+    pub fn cid(&self) -> Cid {
+        let cid = &self.card.cid;
+
+        let mut name = heapless::String::new();
+        for c in cid.name.iter().take_while(|c| **c != 0) {
+            let _ = name.push(*c as u8 as char);
+        }
+
+        Cid {
+            manufacturer_id: cid.mfg_id as _,
+            oem_id: cid.oem_id as _,
+            name,
+            revision: cid.revision as _,
+            serial: cid.serial as _,
+            date: cid.date as _,
+        }
+    }
+
+    pub fn csd(&self) -> Csd {
+        let csd = &self.card.csd;
+
+        Csd {
+            version: csd.csd_ver as _,
+            mmc_version: csd.mmc_ver as _,
+            capacity: csd.capacity as _,
+            sector_size: csd.sector_size as _,
+            read_block_len: csd.read_block_len as _,
+            card_command_class: csd.card_command_class as _,
+            transfer_speed: csd.tr_speed as _,
+        }
+    }
+
+    /// Access to the underlying ESP-IDF card descriptor, e.g. for `esp_vfs_fat_register`
+    pub fn card(&mut self) -> &mut sdmmc_card_t {
+        &mut self.card
+    }
+
+    fn check_buffer(&self, len: usize) -> Result<usize, EspError> {
+        let block_size = self.card.csd.sector_size as usize;
+
+        if block_size == 0 || len % block_size != 0 {
+            return Err(EspError::from(ESP_ERR_INVALID_SIZE).unwrap());
+        }
+
+        Ok(len / block_size)
+    }
+}
+
+impl<H: SdHost> BlockDevice for SdCardDriver<H> {
+    type Error = EspError;
+
+    fn block_size(&self) -> usize {
+        self.card.csd.sector_size as _
+    }
+
+    fn block_count(&self) -> u64 {
+        self.card.csd.capacity as _
+    }
+
+    fn read_blocks(&mut self, start: u64, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let count = self.check_buffer(buf.len())?;
+
+        if count > 0 {
+            esp!(unsafe {
+                sdmmc_read_sectors(
+                    &mut self.card,
+                    buf.as_mut_ptr() as *mut _,
+                    start as _,
+                    count as _,
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn write_blocks(&mut self, start: u64, buf: &[u8]) -> Result<(), Self::Error> {
+        let count = self.check_buffer(buf.len())?;
+
+        if count > 0 {
+            esp!(unsafe {
+                sdmmc_write_sectors(
+                    &mut self.card,
+                    buf.as_ptr() as *const _,
+                    start as _,
+                    count as _,
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+unsafe impl<H: SdHost + Send> Send for SdCardDriver<H> {}
+
+/// Adapts a `BlockDevice` to `std::io::Read + Write + Seek`, caching the block
+/// at the current position
+///
+/// Modified data is written back when the position moves to another block, on `flush`,
+/// and - ignoring any error - on drop.
+#[cfg(feature = "std")]
+pub struct BlockDeviceStream<D: BlockDevice> {
+    device: D,
+    position: u64,
+    block: Option<u64>,
+    dirty: bool,
+    buffer: std::vec::Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<D> BlockDeviceStream<D>
+where
+    D: BlockDevice,
+    D::Error: std::error::Error + Send + Sync + 'static,
+{
+    pub fn new(device: D) -> Self {
+        let block_size = device.block_size();
+
+        Self {
+            device,
+            position: 0,
+            block: None,
+            dirty: false,
+            buffer: std::vec![0; block_size],
+        }
+    }
+
+    pub fn device(&mut self) -> &mut D {
+        &mut self.device
+    }
+
+    pub fn len(&self) -> u64 {
+        self.device.block_count() * self.buffer.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes back the cached block and returns the device
+    pub fn release(mut self) -> std::io::Result<D> {
+        self.write_back()?;
+
+        let this = core::mem::ManuallyDrop::new(self);
+
+        // Safety: `this` is never dropped, so `device` and `buffer` are moved out exactly once
+        let (device, _buffer) =
+            unsafe { (core::ptr::read(&this.device), core::ptr::read(&this.buffer)) };
+
+        Ok(device)
+    }
+
+    fn write_back(&mut self) -> std::io::Result<()> {
+        if let (Some(block), true) = (self.block, self.dirty) {
+            self.device
+                .write_blocks(block, &self.buffer)
+                .map_err(Self::to_io_err)?;
+
+            self.dirty = false;
+        }
+
+        Ok(())
+    }
+
+    /// Makes the block at the current position the cached one and returns the offset within it
+    fn load(&mut self) -> std::io::Result<usize> {
+        let block_size = self.buffer.len() as u64;
+        let block = self.position / block_size;
+
+        if block >= self.device.block_count() {
+            return Ok(block_size as usize);
+        }
+
+        if self.block != Some(block) {
+            self.write_back()?;
+
+            self.block = None;
+            self.device
+                .read_blocks(block, &mut self.buffer)
+                .map_err(Self::to_io_err)?;
+            self.block = Some(block);
+        }
+
+        Ok((self.position % block_size) as usize)
+    }
+
+    fn to_io_err(err: D::Error) -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::Other, err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D> std::io::Read for BlockDeviceStream<D>
+where
+    D: BlockDevice,
+    D::Error: std::error::Error + Send + Sync + 'static,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let offset = self.load()?;
+        let len = buf.len().min(self.buffer.len() - offset);
+
+        buf[..len].copy_from_slice(&self.buffer[offset..offset + len]);
+        self.position += len as u64;
+
+        Ok(len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D> std::io::Write for BlockDeviceStream<D>
+where
+    D: BlockDevice,
+    D::Error: std::error::Error + Send + Sync + 'static,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let offset = self.load()?;
+        let len = buf.len().min(self.buffer.len() - offset);
+
+        if len > 0 {
+            self.buffer[offset..offset + len].copy_from_slice(&buf[..len]);
+            self.dirty = true;
+            self.position += len as u64;
+        }
+
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write_back()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D> std::io::Seek for BlockDeviceStream<D>
+where
+    D: BlockDevice,
+    D::Error: std::error::Error + Send + Sync + 'static,
+{
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            std::io::SeekFrom::Start(offset) => Some(offset),
+            std::io::SeekFrom::End(offset) => add_offset(self.len(), offset),
+            std::io::SeekFrom::Current(offset) => add_offset(self.position, offset),
+        };
+
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: BlockDevice> Drop for BlockDeviceStream<D> {
+    fn drop(&mut self) {
+        if let (Some(block), true) = (self.block, self.dirty) {
+            let _ = self.device.write_blocks(block, &self.buffer);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn add_offset(position: u64, offset: i64) -> Option<u64> {
+    if offset >= 0 {
+        position.checked_add(offset as u64)
+    } else {
+        position.checked_sub(offset.unsigned_abs())
+    }
+}
+
+#[cfg(any(esp32, esp32s3))]
+crate::impl_peripheral!(SDMMC);
@@ -85,6 +85,7 @@ pub type SpiMasterConfig = config::Config;
 
 /// SPI configuration
 pub mod config {
+    use crate::interrupt::InterruptConfig;
     use crate::spi::Dma;
     use crate::units::*;
 
@@ -132,6 +133,7 @@ pub mod config {
         /// See https://docs.espressif.com/projects/esp-idf/en/latest/esp32/api-reference/peripherals/spi_master.html#timing-considerations
         pub write_only: bool,
         pub dma: Dma,
+        pub interrupt: InterruptConfig,
     }
 
     impl Config {
@@ -160,6 +162,12 @@ pub mod config {
             self.dma = dma;
             self
         }
+
+        #[must_use]
+        pub fn interrupt(mut self, interrupt: InterruptConfig) -> Self {
+            self.interrupt = interrupt;
+            self
+        }
     }
 
     impl Default for Config {
@@ -169,6 +177,7 @@ pub mod config {
                 data_mode: embedded_hal::spi::MODE_0,
                 write_only: false,
                 dma: Dma::Disabled,
+                interrupt: Default::default(),
             }
         }
     }
@@ -376,6 +385,7 @@ impl<'d, SPI: Spi> SpiMasterDriver<'d, SPI> {
                 //data3_io_num: -1,
             },
             max_transfer_sz: config.dma.max_transfer_size() as i32,
+            intr_flags: config.interrupt.into(),
             ..Default::default()
         };
 
@@ -390,6 +400,7 @@ impl<'d, SPI: Spi> SpiMasterDriver<'d, SPI> {
             quadhd_io_num: -1,
 
             max_transfer_sz: config.dma.max_transfer_size() as i32,
+            intr_flags: config.interrupt.into(),
             ..Default::default()
         };
 
@@ -235,6 +235,238 @@ pub mod thread {
     }
 }
 
+#[cfg(all(feature = "alloc", esp_idf_freertos_use_trace_facility))]
+pub mod stats {
+    extern crate alloc;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use esp_idf_sys::*;
+
+    use crate::cpu::{Core, CORES};
+
+    /// The type FreeRTOS keeps its run time stats counters in (`configRUN_TIME_COUNTER_TYPE`)
+    #[cfg(esp_idf_freertos_run_time_counter_type_u64)]
+    type RunTimeCounter = u64;
+    #[cfg(not(esp_idf_freertos_run_time_counter_type_u64))]
+    type RunTimeCounter = u32;
+
+    #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+    pub enum TaskState {
+        Running,
+        Ready,
+        Blocked,
+        Suspended,
+        Deleted,
+        Invalid,
+    }
+
+    #[allow(non_upper_case_globals)]
+    impl From<eTaskState> for TaskState {
+        fn from(state: eTaskState) -> Self {
+            match state {
+                eTaskState_eRunning => TaskState::Running,
+                eTaskState_eReady => TaskState::Ready,
+                eTaskState_eBlocked => TaskState::Blocked,
+                eTaskState_eSuspended => TaskState::Suspended,
+                eTaskState_eDeleted => TaskState::Deleted,
+                _ => TaskState::Invalid,
+            }
+        }
+    }
+
+    /// A point-in-time view of a single FreeRTOS task
+    #[derive(Clone, Debug)]
+    pub struct TaskInfo {
+        pub handle: TaskHandle_t,
+        pub name: String,
+        pub state: TaskState,
+        pub priority: u32,
+        pub base_priority: u32,
+        /// The core the task is pinned to, or `None` if the task can run on any core
+        pub core: Option<Core>,
+        /// The minimum amount of stack (in bytes) that remained free since the task started
+        pub stack_high_water_mark: u32,
+        /// Accumulated run time, in units of the FreeRTOS run time stats clock
+        pub runtime: u64,
+    }
+
+    /// A snapshot of all tasks currently known to the FreeRTOS scheduler
+    #[derive(Clone, Debug)]
+    pub struct TasksSnapshot {
+        pub tasks: Vec<TaskInfo>,
+        /// Total elapsed time of the run time stats clock, to which `TaskInfo::runtime` can be related
+        pub total_runtime: u64,
+    }
+
+    impl TasksSnapshot {
+        pub fn task(&self, handle: TaskHandle_t) -> Option<&TaskInfo> {
+            self.tasks.iter().find(|task| task.handle == handle)
+        }
+    }
+
+    /// Returns a snapshot of all tasks (name, state, priority, core, stack high-water mark and run time).
+    ///
+    /// Requires `CONFIG_FREERTOS_USE_TRACE_FACILITY`. Run time counters are only populated
+    /// when `CONFIG_FREERTOS_GENERATE_RUN_TIME_STATS` is enabled too, and are zero otherwise.
+    pub fn snapshot() -> TasksSnapshot {
+        let mut statuses: Vec<TaskStatus_t> = Vec::new();
+        let mut total_runtime: RunTimeCounter = 0;
+
+        loop {
+            // Leave some room for tasks that might get created in the meantime
+            let capacity = unsafe { uxTaskGetNumberOfTasks() } as usize + 4;
+
+            statuses.clear();
+            statuses.reserve(capacity);
+
+            let count = unsafe {
+                uxTaskGetSystemState(statuses.as_mut_ptr(), capacity as _, &mut total_runtime)
+            } as usize;
+
+            // `uxTaskGetSystemState` returns 0 if the supplied array was too small
+            if count > 0 {
+                unsafe {
+                    statuses.set_len(count);
+                }
+
+                break;
+            }
+        }
+
+        TasksSnapshot {
+            tasks: statuses.iter().map(Into::into).collect(),
+            total_runtime: total_runtime.into(),
+        }
+    }
+
+    impl From<&TaskStatus_t> for TaskInfo {
+        fn from(status: &TaskStatus_t) -> Self {
+            Self {
+                handle: status.xHandle,
+                name: if status.pcTaskName.is_null() {
+                    String::new()
+                } else {
+                    let name = unsafe {
+                        core::slice::from_raw_parts(
+                            status.pcTaskName as *const u8,
+                            strlen(status.pcTaskName) as usize,
+                        )
+                    };
+
+                    String::from_utf8_lossy(name).into_owned()
+                },
+                state: status.eCurrentState.into(),
+                priority: status.uxCurrentPriority as _,
+                base_priority: status.uxBasePriority as _,
+                #[cfg(esp_idf_freertos_vtasklist_include_coreid)]
+                core: match status.xCoreID {
+                    0 => Some(Core::Core0),
+                    #[cfg(any(esp32, esp32s3))]
+                    1 => Some(Core::Core1),
+                    _ => None,
+                },
+                #[cfg(not(esp_idf_freertos_vtasklist_include_coreid))]
+                core: None,
+                stack_high_water_mark: status.usStackHighWaterMark as _,
+                runtime: status.ulRunTimeCounter.into(),
+            }
+        }
+    }
+
+    /// CPU utilisation of each core, in percent, over the last sampling interval
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct CpuLoad([f32; CORES as usize]);
+
+    impl CpuLoad {
+        pub fn core(&self, core: Core) -> f32 {
+            self.0[core as usize]
+        }
+
+        pub fn cores(&self) -> &[f32] {
+            &self.0
+        }
+    }
+
+    /// Measures the CPU utilisation of each core by sampling the run time
+    /// of the per-core FreeRTOS IDLE tasks.
+    ///
+    /// Requires `CONFIG_FREERTOS_GENERATE_RUN_TIME_STATS`.
+    ///
+    /// The load reported by `sample` relates to the time elapsed since the
+    /// previous call to `sample` (or since the monitor was created).
+    #[cfg(esp_idf_freertos_generate_run_time_stats)]
+    pub struct CpuLoadMonitor {
+        idle_tasks: [TaskHandle_t; CORES as usize],
+        idle_runtime: [RunTimeCounter; CORES as usize],
+        total_runtime: RunTimeCounter,
+    }
+
+    #[cfg(esp_idf_freertos_generate_run_time_stats)]
+    impl CpuLoadMonitor {
+        pub fn new() -> Self {
+            let mut idle_tasks = [core::ptr::null_mut(); CORES as usize];
+
+            for (cpu, idle_task) in idle_tasks.iter_mut().enumerate() {
+                *idle_task = unsafe { xTaskGetIdleTaskHandleForCPU(cpu as _) };
+            }
+
+            let mut this = Self {
+                idle_tasks,
+                idle_runtime: [0; CORES as usize],
+                total_runtime: 0,
+            };
+
+            this.sample();
+
+            this
+        }
+
+        pub fn sample(&mut self) -> CpuLoad {
+            self.sample_from(&snapshot())
+        }
+
+        /// Same as `sample`, but re-uses a snapshot which was already taken by the caller
+        pub fn sample_from(&mut self, snapshot: &TasksSnapshot) -> CpuLoad {
+            // The snapshot values are widened counters, so they wrap at the width of `RunTimeCounter`
+            let total_runtime = snapshot.total_runtime as RunTimeCounter;
+
+            let total_delta = total_runtime.wrapping_sub(self.total_runtime);
+            self.total_runtime = total_runtime;
+
+            let mut load = [0_f32; CORES as usize];
+
+            for (cpu, idle_task) in self.idle_tasks.iter().enumerate() {
+                let idle_runtime = snapshot
+                    .task(*idle_task)
+                    .map(|task| task.runtime as RunTimeCounter)
+                    .unwrap_or(self.idle_runtime[cpu]);
+
+                let idle_delta = idle_runtime.wrapping_sub(self.idle_runtime[cpu]);
+                self.idle_runtime[cpu] = idle_runtime;
+
+                if total_delta > 0 {
+                    let idle = (idle_delta as f32 / total_delta as f32).min(1.0);
+
+                    load[cpu] = (1.0 - idle) * 100.0;
+                }
+            }
+
+            CpuLoad(load)
+        }
+    }
+
+    #[cfg(esp_idf_freertos_generate_run_time_stats)]
+    impl Default for CpuLoadMonitor {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    #[cfg(esp_idf_freertos_generate_run_time_stats)]
+    unsafe impl Send for CpuLoadMonitor {}
+}
+
 pub struct CriticalSection(UnsafeCell<MaybeUninit<StaticQueue_t>>, AtomicBool);
 
 // Not available in the esp-idf-sys bindings
@@ -0,0 +1,232 @@
+//! Internal temperature sensor (ESP32-S2, ESP32-S3 and ESP32-C3)
+//!
+//! The sensor is accurate only within the selected measurement range; the readings
+//! are calibrated by ESP-IDF with the factory calibration stored in the eFuses.
+//!
+//! # Examples
+//!
+//! ```
+//! use esp_idf_hal::peripherals::Peripherals;
+//! use esp_idf_hal::temp_sensor::{config::Config, TempSensorDriver};
+//!
+//! let peripherals = Peripherals::take().unwrap();
+//! let mut sensor = TempSensorDriver::new(peripherals.temp_sensor, &Config::default())?;
+//!
+//! sensor.enable()?;
+//! let celsius = sensor.get_celsius()?;
+//! ```
+
+use esp_idf_sys::*;
+
+use crate::peripheral::{Peripheral, PeripheralRef};
+
+/// Types for configuring the temperature sensor
+pub mod config {
+    /// Measurement ranges supported by the sensor, in degrees Celsius
+    ///
+    /// The narrower the range, the smaller the measurement error.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum MeasurementRange {
+        /// 50 ~ 125 °C
+        Celsius50To125,
+        /// 20 ~ 100 °C
+        Celsius20To100,
+        /// -10 ~ 80 °C
+        CelsiusMinus10To80,
+        /// -30 ~ 50 °C
+        CelsiusMinus30To50,
+        /// -40 ~ 20 °C
+        CelsiusMinus40To20,
+    }
+
+    impl MeasurementRange {
+        pub fn min(&self) -> i32 {
+            match self {
+                Self::Celsius50To125 => 50,
+                Self::Celsius20To100 => 20,
+                Self::CelsiusMinus10To80 => -10,
+                Self::CelsiusMinus30To50 => -30,
+                Self::CelsiusMinus40To20 => -40,
+            }
+        }
+
+        pub fn max(&self) -> i32 {
+            match self {
+                Self::Celsius50To125 => 125,
+                Self::Celsius20To100 => 100,
+                Self::CelsiusMinus10To80 => 80,
+                Self::CelsiusMinus30To50 => 50,
+                Self::CelsiusMinus40To20 => 20,
+            }
+        }
+    }
+
+    impl Default for MeasurementRange {
+        fn default() -> Self {
+            Self::CelsiusMinus10To80
+        }
+    }
+
+    #[cfg(esp_idf_version_major = "4")]
+    impl From<MeasurementRange> for esp_idf_sys::temp_sensor_dac_offset_t {
+        fn from(range: MeasurementRange) -> Self {
+            match range {
+                MeasurementRange::Celsius50To125 => {
+                    esp_idf_sys::temp_sensor_dac_offset_t_TSENS_DAC_L0
+                }
+                MeasurementRange::Celsius20To100 => {
+                    esp_idf_sys::temp_sensor_dac_offset_t_TSENS_DAC_L1
+                }
+                MeasurementRange::CelsiusMinus10To80 => {
+                    esp_idf_sys::temp_sensor_dac_offset_t_TSENS_DAC_L2
+                }
+                MeasurementRange::CelsiusMinus30To50 => {
+                    esp_idf_sys::temp_sensor_dac_offset_t_TSENS_DAC_L3
+                }
+                MeasurementRange::CelsiusMinus40To20 => {
+                    esp_idf_sys::temp_sensor_dac_offset_t_TSENS_DAC_L4
+                }
+            }
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, Default)]
+    pub struct Config {
+        pub range: MeasurementRange,
+    }
+
+    impl Config {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        #[must_use]
+        pub fn range(mut self, range: MeasurementRange) -> Self {
+            self.range = range;
+            self
+        }
+    }
+}
+
+pub struct TempSensorDriver<'d> {
+    _sensor: PeripheralRef<'d, TEMP_SENSOR>,
+    #[cfg(not(esp_idf_version_major = "4"))]
+    handle: temperature_sensor_handle_t,
+    enabled: bool,
+}
+
+impl<'d> TempSensorDriver<'d> {
+    /// Installs the sensor with the measurement range of `config`; the sensor starts disabled
+    #[cfg(esp_idf_version_major = "4")]
+    pub fn new(
+        sensor: impl Peripheral<P = TEMP_SENSOR> + 'd,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(sensor);
+
+        let sys_config = temp_sensor_config_t {
+            dac_offset: config.range.into(),
+            clk_div: 6,
+        };
+
+        esp!(unsafe { temp_sensor_set_config(sys_config) })?;
+
+        Ok(Self {
+            _sensor: sensor,
+            enabled: false,
+        })
+    }
+
+    /// Installs the sensor with the measurement range of `config`; the sensor starts disabled
+    #[cfg(not(esp_idf_version_major = "4"))]
+    pub fn new(
+        sensor: impl Peripheral<P = TEMP_SENSOR> + 'd,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(sensor);
+
+        let sys_config = temperature_sensor_config_t {
+            range_min: config.range.min(),
+            range_max: config.range.max(),
+            clk_src: soc_periph_temperature_sensor_clk_src_t_TEMPERATURE_SENSOR_CLK_SRC_DEFAULT,
+        };
+
+        let mut handle: temperature_sensor_handle_t = core::ptr::null_mut();
+
+        esp!(unsafe { temperature_sensor_install(&sys_config, &mut handle) })?;
+
+        Ok(Self {
+            _sensor: sensor,
+            handle,
+            enabled: false,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn enable(&mut self) -> Result<(), EspError> {
+        if !self.enabled {
+            #[cfg(esp_idf_version_major = "4")]
+            esp!(unsafe { temp_sensor_start() })?;
+
+            #[cfg(not(esp_idf_version_major = "4"))]
+            esp!(unsafe { temperature_sensor_enable(self.handle) })?;
+
+            self.enabled = true;
+        }
+
+        Ok(())
+    }
+
+    pub fn disable(&mut self) -> Result<(), EspError> {
+        if self.enabled {
+            #[cfg(esp_idf_version_major = "4")]
+            esp!(unsafe { temp_sensor_stop() })?;
+
+            #[cfg(not(esp_idf_version_major = "4"))]
+            esp!(unsafe { temperature_sensor_disable(self.handle) })?;
+
+            self.enabled = false;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the calibrated temperature in degrees Celsius
+    ///
+    /// Returns `ESP_ERR_INVALID_STATE` if the sensor is not enabled.
+    pub fn get_celsius(&self) -> Result<f32, EspError> {
+        if !self.enabled {
+            return Err(EspError::from(ESP_ERR_INVALID_STATE).unwrap());
+        }
+
+        let mut celsius = 0.0;
+
+        #[cfg(esp_idf_version_major = "4")]
+        esp!(unsafe { temp_sensor_read_celsius(&mut celsius) })?;
+
+        #[cfg(not(esp_idf_version_major = "4"))]
+        esp!(unsafe { temperature_sensor_get_celsius(self.handle, &mut celsius) })?;
+
+        Ok(celsius)
+    }
+
+    pub fn get_fahrenheit(&self) -> Result<f32, EspError> {
+        Ok(self.get_celsius()? * 1.8 + 32.0)
+    }
+}
+
+impl<'d> Drop for TempSensorDriver<'d> {
+    fn drop(&mut self) {
+        self.disable().unwrap();
+
+        #[cfg(not(esp_idf_version_major = "4"))]
+        esp!(unsafe { temperature_sensor_uninstall(self.handle) }).unwrap();
+    }
+}
+
+unsafe impl<'d> Send for TempSensorDriver<'d> {}
+
+crate::impl_peripheral!(TEMP_SENSOR);
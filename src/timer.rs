@@ -1,6 +1,12 @@
+use core::marker::PhantomData;
+use core::time::Duration;
+
 use esp_idf_sys::*;
 
+use crate::interrupt::asynch::HalIsrNotification;
+use crate::interrupt::InterruptConfig;
 use crate::peripheral::{Peripheral, PeripheralRef};
+use crate::units::Hertz;
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
@@ -12,11 +18,41 @@ pub type TimerConfig = config::Config;
 
 /// Timer configuration
 pub mod config {
+    use esp_idf_sys::*;
+
+    use crate::interrupt::InterruptConfig;
+
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum CountDirection {
+        Up,
+        Down,
+    }
+
+    impl Default for CountDirection {
+        fn default() -> Self {
+            Self::Up
+        }
+    }
+
+    impl From<CountDirection> for timer_count_dir_t {
+        fn from(direction: CountDirection) -> Self {
+            match direction {
+                CountDirection::Up => timer_count_dir_t_TIMER_COUNT_UP,
+                CountDirection::Down => timer_count_dir_t_TIMER_COUNT_DOWN,
+            }
+        }
+    }
+
     #[derive(Copy, Clone)]
     pub struct Config {
         pub divider: u32,
         #[cfg(any(esp32s2, esp32s3, esp32c3))]
         pub xtal: bool,
+        /// When enabled, the counter is reloaded with the reload value
+        /// (the last value passed to `TimerDriver::set_counter`) each time the alarm triggers
+        pub auto_reload: bool,
+        pub direction: CountDirection,
+        pub interrupt: InterruptConfig,
     }
 
     impl Config {
@@ -36,6 +72,24 @@ pub mod config {
             self.xtal = xtal;
             self
         }
+
+        #[must_use]
+        pub fn auto_reload(mut self, auto_reload: bool) -> Self {
+            self.auto_reload = auto_reload;
+            self
+        }
+
+        #[must_use]
+        pub fn direction(mut self, direction: CountDirection) -> Self {
+            self.direction = direction;
+            self
+        }
+
+        #[must_use]
+        pub fn interrupt(mut self, interrupt: InterruptConfig) -> Self {
+            self.interrupt = interrupt;
+            self
+        }
     }
 
     impl Default for Config {
@@ -44,6 +98,9 @@ pub mod config {
                 divider: 80,
                 #[cfg(any(esp32s2, esp32s3, esp32c3))]
                 xtal: false,
+                auto_reload: false,
+                direction: CountDirection::Up,
+                interrupt: Default::default(),
             }
         }
     }
@@ -54,11 +111,20 @@ pub trait Timer: Send {
     fn index() -> timer_idx_t;
 }
 
+const APB_CLK_HZ: u32 = 80_000_000;
+
+#[cfg(any(esp32s2, esp32s3, esp32c3))]
+const XTAL_CLK_HZ: u32 = 40_000_000;
+
 pub struct TimerDriver<'d, TIMER>
 where
     TIMER: Timer,
 {
     _timer: PeripheralRef<'d, TIMER>,
+    tick_hz: Hertz,
+    direction: config::CountDirection,
+    interrupt: InterruptConfig,
+    isr_registered: bool,
 }
 
 impl<'d, TIMER> TimerDriver<'d, TIMER>
@@ -78,8 +144,12 @@ where
                 &timer_config_t {
                     alarm_en: timer_alarm_t_TIMER_ALARM_DIS,
                     counter_en: timer_start_t_TIMER_PAUSE,
-                    counter_dir: timer_count_dir_t_TIMER_COUNT_UP,
-                    auto_reload: timer_autoreload_t_TIMER_AUTORELOAD_DIS,
+                    counter_dir: config.direction.into(),
+                    auto_reload: if config.auto_reload {
+                        timer_autoreload_t_TIMER_AUTORELOAD_EN
+                    } else {
+                        timer_autoreload_t_TIMER_AUTORELOAD_DIS
+                    },
                     intr_type: timer_intr_mode_t_TIMER_INTR_LEVEL,
                     divider: config.divider,
                     #[cfg(all(any(esp32s2, esp32s3, esp32c3), esp_idf_version_major = "4"))]
@@ -94,7 +164,24 @@ where
             )
         })?;
 
-        Ok(TimerDriver { _timer: timer })
+        #[cfg(any(esp32s2, esp32s3, esp32c3))]
+        let src_hz = if config.xtal { XTAL_CLK_HZ } else { APB_CLK_HZ };
+
+        #[cfg(not(any(esp32s2, esp32s3, esp32c3)))]
+        let src_hz = APB_CLK_HZ;
+
+        Ok(TimerDriver {
+            _timer: timer,
+            tick_hz: Hertz(src_hz / config.divider),
+            direction: config.direction,
+            interrupt: config.interrupt,
+            isr_registered: false,
+        })
+    }
+
+    /// Returns the frequency at which the timer counter is incremented (or decremented)
+    pub fn tick_hz(&self) -> Hertz {
+        self.tick_hz
     }
 
     pub fn enable(&mut self, enable: bool) -> Result<(), EspError> {
@@ -125,6 +212,16 @@ where
         Ok(value)
     }
 
+    /// Returns the current counter value converted to a `Duration` using the timer tick rate
+    pub fn counter_as_duration(&self) -> Result<Duration, EspError> {
+        Ok(self.ticks_to_duration(self.counter()?))
+    }
+
+    /// Sets the counter value.
+    ///
+    /// The value is also latched as the reload value, i.e. this is the value the counter
+    /// gets reset to each time the alarm triggers when auto-reload is enabled;
+    /// use `set_reload_value` afterwards to reload to a different value.
     pub fn set_counter(&mut self, value: u64) -> Result<(), EspError> {
         self.check();
 
@@ -133,6 +230,55 @@ where
         Ok(())
     }
 
+    /// Sets the value the counter gets reset to each time the alarm triggers when auto-reload
+    /// is enabled, without changing the current counter value.
+    pub fn set_reload_value(&mut self, value: u64) -> Result<(), EspError> {
+        self.check();
+
+        let base = if TIMER::group() == timer_group_t_TIMER_GROUP_0 {
+            DR_REG_TIMERGROUP0_BASE
+        } else {
+            DR_REG_TIMERGROUP1_BASE
+        } as usize
+            + TIMER::index() as usize * regs::TIMER_STRIDE;
+
+        // Only writing the `LOAD` register would copy the reload value into the counter
+        unsafe {
+            core::ptr::write_volatile((base + regs::LOADLO) as *mut u32, value as u32);
+            core::ptr::write_volatile((base + regs::LOADHI) as *mut u32, (value >> 32) as u32);
+        }
+
+        Ok(())
+    }
+
+    pub fn set_auto_reload(&mut self, auto_reload: bool) -> Result<(), EspError> {
+        self.check();
+
+        esp!(unsafe {
+            timer_set_auto_reload(
+                TIMER::group(),
+                TIMER::index(),
+                if auto_reload {
+                    timer_autoreload_t_TIMER_AUTORELOAD_EN
+                } else {
+                    timer_autoreload_t_TIMER_AUTORELOAD_DIS
+                },
+            )
+        })?;
+
+        Ok(())
+    }
+
+    pub fn set_direction(&mut self, direction: config::CountDirection) -> Result<(), EspError> {
+        self.check();
+
+        esp!(unsafe { timer_set_counter_mode(TIMER::group(), TIMER::index(), direction.into()) })?;
+
+        self.direction = direction;
+
+        Ok(())
+    }
+
     pub fn enable_alarm(&mut self, enable: bool) -> Result<(), EspError> {
         if crate::interrupt::active() {
             if enable {
@@ -181,9 +327,19 @@ where
         Ok(())
     }
 
+    /// Sets the alarm to the counter value which corresponds to `duration`.
+    ///
+    /// Combined with auto-reload and a reload value of 0, this results in
+    /// a periodic alarm with a period of `duration`.
+    pub fn set_alarm_duration(&mut self, duration: Duration) -> Result<(), EspError> {
+        self.set_alarm(self.duration_to_ticks(duration))
+    }
+
     pub fn enable_interrupt(&mut self) -> Result<(), EspError> {
         self.check();
 
+        self.register_isr()?;
+
         esp!(unsafe { timer_enable_intr(TIMER::group(), TIMER::index()) })?;
 
         Ok(())
@@ -197,6 +353,46 @@ where
         Ok(())
     }
 
+    /// Asynchronously waits for `duration` to elapse, using the timer alarm.
+    ///
+    /// The timer should be enabled, and should not be used for anything else while this future is pending.
+    pub async fn delay(&mut self, duration: Duration) -> Result<(), EspError> {
+        let counter = self.counter()?;
+        let ticks = self.duration_to_ticks(duration);
+
+        let alarm = match self.direction {
+            config::CountDirection::Up => counter.saturating_add(ticks),
+            config::CountDirection::Down => counter.saturating_sub(ticks),
+        };
+
+        Self::notification().reset();
+
+        self.set_alarm(alarm)?;
+        self.enable_interrupt()?;
+        self.enable_alarm(true)?;
+
+        let guard = AlarmGuard::<TIMER>(PhantomData);
+
+        Self::notification().wait().await;
+
+        core::mem::forget(guard);
+
+        Ok(())
+    }
+
+    /// Asynchronously waits for the next alarm to trigger.
+    ///
+    /// Useful with auto-reload enabled, for driving periodic control loops.
+    pub async fn wait_alarm(&mut self) -> Result<(), EspError> {
+        Self::notification().reset();
+
+        self.enable_interrupt()?;
+
+        Self::notification().wait().await;
+
+        Ok(())
+    }
+
     /// # Safety
     ///
     /// Care should be taken not to call STD, libc or FreeRTOS APIs (except for a few allowed ones)
@@ -209,22 +405,15 @@ where
 
         let callback: Box<dyn FnMut() + 'static> = Box::new(callback);
 
-        ISR_HANDLERS[(TIMER::group() * timer_group_t_TIMER_GROUP_MAX + TIMER::index()) as usize] =
-            Some(Box::new(callback));
-
-        esp!(timer_isr_callback_add(
-            TIMER::group(),
-            TIMER::index(),
-            Some(Self::handle_isr),
-            UnsafeCallback::from(
-                ISR_HANDLERS
-                    [(TIMER::group() * timer_group_t_TIMER_GROUP_MAX + TIMER::index()) as usize]
-                    .as_mut()
-                    .unwrap(),
-            )
-            .as_ptr(),
-            0
-        ))?;
+        // Once registered, the ISR stays registered for the rest of the lifetime of the driver,
+        // so keep it from running - on this or the other core - while the handler is being replaced
+        self.disable_interrupt()?;
+
+        {
+            let _guard = ISR_HANDLERS_CS.enter();
+
+            ISR_HANDLERS[Self::timer_index()] = Some(callback);
+        }
 
         self.enable_interrupt()?;
 
@@ -236,35 +425,61 @@ where
         self.check();
 
         unsafe {
-            let subscribed = ISR_HANDLERS
-                [(TIMER::group() * timer_group_t_TIMER_GROUP_MAX + TIMER::index()) as usize]
-                .is_some();
+            let subscribed = ISR_HANDLERS[Self::timer_index()].is_some();
 
             if subscribed {
                 esp!(timer_disable_intr(TIMER::group(), TIMER::index()))?;
-                esp!(timer_isr_callback_remove(TIMER::group(), TIMER::index()))?;
 
-                ISR_HANDLERS
-                    [(TIMER::group() * timer_group_t_TIMER_GROUP_MAX + TIMER::index()) as usize] =
-                    None;
+                let _guard = ISR_HANDLERS_CS.enter();
+
+                ISR_HANDLERS[Self::timer_index()] = None;
             }
         }
 
         Ok(())
     }
 
+    /// Registers the ISR on first use, so that timers which never enable their
+    /// interrupt do not take up an interrupt slot
+    fn register_isr(&mut self) -> Result<(), EspError> {
+        if !self.isr_registered {
+            esp!(unsafe {
+                timer_isr_callback_add(
+                    TIMER::group(),
+                    TIMER::index(),
+                    Some(handle_isr),
+                    Self::timer_index() as _,
+                    self.interrupt.into(),
+                )
+            })?;
+
+            self.isr_registered = true;
+        }
+
+        Ok(())
+    }
+
+    fn duration_to_ticks(&self, duration: Duration) -> u64 {
+        (duration.as_nanos() * self.tick_hz.0 as u128 / 1_000_000_000) as u64
+    }
+
+    fn ticks_to_duration(&self, ticks: u64) -> Duration {
+        Duration::from_nanos((ticks as u128 * 1_000_000_000 / self.tick_hz.0 as u128) as u64)
+    }
+
+    fn timer_index() -> usize {
+        (TIMER::group() * timer_idx_t_TIMER_MAX + TIMER::index()) as _
+    }
+
+    fn notification() -> &'static HalIsrNotification {
+        &NOTIFICATIONS[Self::timer_index()]
+    }
+
     fn check(&self) {
         if crate::interrupt::active() {
             panic!("This function cannot be called from an ISR");
         }
     }
-
-    #[cfg(feature = "alloc")]
-    unsafe extern "C" fn handle_isr(unsafe_callback: *mut c_types::c_void) -> bool {
-        crate::interrupt::with_isr_yield_signal(move || {
-            UnsafeCallback::from_ptr(unsafe_callback).call();
-        })
-    }
 }
 
 impl<'d, TIMER: Timer> Drop for TimerDriver<'d, TIMER> {
@@ -274,35 +489,55 @@ impl<'d, TIMER: Timer> Drop for TimerDriver<'d, TIMER> {
             self.unsubscribe().unwrap();
         }
 
+        if self.isr_registered {
+            esp!(unsafe { timer_isr_callback_remove(TIMER::group(), TIMER::index()) }).unwrap();
+        }
+
         esp!(unsafe { timer_deinit(TIMER::group(), TIMER::index()) }).unwrap();
     }
 }
 
 unsafe impl<'d, TIMER: Timer> Send for TimerDriver<'d, TIMER> {}
 
-#[cfg(feature = "alloc")]
-struct UnsafeCallback(*mut Box<dyn FnMut() + 'static>);
+/// Disarms the alarm and the interrupt of a `delay` future which is dropped before completing
+struct AlarmGuard<TIMER: Timer>(PhantomData<TIMER>);
 
-#[cfg(feature = "alloc")]
-impl UnsafeCallback {
-    #[allow(clippy::type_complexity)]
-    pub fn from(boxed: &mut Box<Box<dyn FnMut() + 'static>>) -> Self {
-        Self(boxed.as_mut())
+impl<TIMER: Timer> Drop for AlarmGuard<TIMER> {
+    fn drop(&mut self) {
+        esp!(unsafe {
+            timer_set_alarm(
+                TIMER::group(),
+                TIMER::index(),
+                timer_alarm_t_TIMER_ALARM_DIS,
+            )
+        })
+        .unwrap();
+        esp!(unsafe { timer_disable_intr(TIMER::group(), TIMER::index()) }).unwrap();
     }
+}
 
-    pub unsafe fn from_ptr(ptr: *mut c_types::c_void) -> Self {
-        Self(ptr as *mut _)
-    }
+unsafe extern "C" fn handle_isr(arg: *mut c_types::c_void) -> bool {
+    let index = arg as usize;
 
-    pub fn as_ptr(&self) -> *mut c_types::c_void {
-        self.0 as *mut _
-    }
+    crate::interrupt::with_isr_yield_signal(move || {
+        #[cfg(feature = "alloc")]
+        {
+            let _guard = ISR_HANDLERS_CS.enter();
 
-    pub unsafe fn call(&mut self) {
-        let reference = self.0.as_mut().unwrap();
+            if let Some(handler) = ISR_HANDLERS[index].as_mut() {
+                handler();
+            }
+        }
 
-        (reference)();
-    }
+        NOTIFICATIONS[index].notify_lsb();
+
+        #[cfg(feature = "embassy-time-isr-queue")]
+        {
+            if index == embassy_time::driver::timer_index() {
+                embassy_time::driver::on_alarm();
+            }
+        }
+    })
 }
 
 macro_rules! impl_timer {
@@ -323,19 +558,269 @@ macro_rules! impl_timer {
     };
 }
 
+#[cfg(esp32c3)]
+static NOTIFICATIONS: [HalIsrNotification; 2] =
+    [HalIsrNotification::new(), HalIsrNotification::new()];
+
+#[cfg(not(esp32c3))]
+static NOTIFICATIONS: [HalIsrNotification; 4] = [
+    HalIsrNotification::new(),
+    HalIsrNotification::new(),
+    HalIsrNotification::new(),
+    HalIsrNotification::new(),
+];
+
 #[allow(clippy::type_complexity)]
 #[cfg(esp32c3)]
 #[cfg(feature = "alloc")]
-static mut ISR_HANDLERS: [Option<Box<Box<dyn FnMut()>>>; 2] = [None, None];
+static mut ISR_HANDLERS: [Option<Box<dyn FnMut()>>; 2] = [None, None];
 
 #[allow(clippy::type_complexity)]
 #[cfg(not(esp32c3))]
 #[cfg(feature = "alloc")]
-static mut ISR_HANDLERS: [Option<Box<Box<dyn FnMut()>>>; 4] = [None, None, None, None];
+static mut ISR_HANDLERS: [Option<Box<dyn FnMut()>>; 4] = [None, None, None, None];
+
+/// Guards `ISR_HANDLERS` against the ISR running on the other core
+#[cfg(feature = "alloc")]
+static ISR_HANDLERS_CS: crate::interrupt::CriticalSection =
+    crate::interrupt::CriticalSection::new();
+
+mod regs {
+    /// Offset between the register blocks of timer 0 and timer 1 of a group
+    pub const TIMER_STRIDE: usize = 0x24;
+
+    pub const LOADLO: usize = 0x18;
+    pub const LOADHI: usize = 0x1c;
+}
 
 impl_timer!(TIMER00: timer_group_t_TIMER_GROUP_0, timer_idx_t_TIMER_0);
 #[cfg(not(esp32c3))]
 impl_timer!(TIMER01: timer_group_t_TIMER_GROUP_0, timer_idx_t_TIMER_1);
 impl_timer!(TIMER10: timer_group_t_TIMER_GROUP_1, timer_idx_t_TIMER_0);
 #[cfg(not(esp32c3))]
-impl_timer!(TIMER11: timer_group_t_TIMER_GROUP_1, timer_idx_t_TIMER_0);
+impl_timer!(TIMER11: timer_group_t_TIMER_GROUP_1, timer_idx_t_TIMER_1);
+
+/// An `embassy-time` driver backed by one of the hardware group timers.
+///
+/// The timer is selected with one of the `embassy-time-isr-queue-timerXX` features;
+/// the selected timer is then no longer available in `Peripherals`.
+///
+/// The driver maintains a queue of alarms which is serviced from the timer ISR:
+/// the hardware alarm is always programmed for the earliest pending alarm.
+#[cfg(feature = "embassy-time-isr-queue")]
+pub mod embassy_time {
+    pub mod driver {
+        use core::cell::UnsafeCell;
+        use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+        use embassy_time::driver::{AlarmHandle, Driver};
+
+        use crate::interrupt::CriticalSection;
+        use crate::timer::{config, TimerDriver};
+
+        #[cfg(feature = "embassy-time-isr-queue-timer00")]
+        type QueueTimer = crate::timer::TIMER00;
+        #[cfg(feature = "embassy-time-isr-queue-timer01")]
+        type QueueTimer = crate::timer::TIMER01;
+        #[cfg(feature = "embassy-time-isr-queue-timer10")]
+        type QueueTimer = crate::timer::TIMER10;
+        #[cfg(feature = "embassy-time-isr-queue-timer11")]
+        type QueueTimer = crate::timer::TIMER11;
+
+        const MAX_ALARMS: usize = 16;
+
+        #[derive(Copy, Clone)]
+        struct Alarm {
+            timestamp: u64,
+            callback: Option<(fn(*mut ()), *mut ())>,
+        }
+
+        impl Alarm {
+            const fn new() -> Self {
+                Self {
+                    timestamp: u64::MAX,
+                    callback: None,
+                }
+            }
+        }
+
+        pub struct IsrQueueTimerDriver {
+            cs: CriticalSection,
+            alarms: UnsafeCell<[Alarm; MAX_ALARMS]>,
+            allocated: AtomicU8,
+            timer: UnsafeCell<Option<TimerDriver<'static, QueueTimer>>>,
+            initialized: AtomicBool,
+        }
+
+        static INIT_CS: crate::task::CriticalSection = crate::task::CriticalSection::new();
+
+        impl IsrQueueTimerDriver {
+            const fn new() -> Self {
+                Self {
+                    cs: CriticalSection::new(),
+                    alarms: UnsafeCell::new([Alarm::new(); MAX_ALARMS]),
+                    allocated: AtomicU8::new(0),
+                    timer: UnsafeCell::new(None),
+                    initialized: AtomicBool::new(false),
+                }
+            }
+
+            #[allow(clippy::mut_from_ref)]
+            fn timer(&self) -> &mut TimerDriver<'static, QueueTimer> {
+                if !self.initialized.load(Ordering::SeqCst) {
+                    if crate::interrupt::active() {
+                        panic!("The embassy-time driver cannot be initialized from an ISR");
+                    }
+
+                    let _guard = INIT_CS.enter();
+
+                    if !self.initialized.load(Ordering::SeqCst) {
+                        // 80MHz APB clock / 80 = 1MHz, i.e. one tick per microsecond
+                        let mut timer = TimerDriver::new(
+                            unsafe { QueueTimer::new() },
+                            &config::Config::new().divider(80),
+                        )
+                        .unwrap();
+
+                        timer.set_counter(0).unwrap();
+                        timer.enable_interrupt().unwrap();
+                        timer.enable(true).unwrap();
+
+                        unsafe {
+                            *self.timer.get() = Some(timer);
+                        }
+
+                        self.initialized.store(true, Ordering::SeqCst);
+                    }
+                }
+
+                unsafe { self.timer.get().as_mut().unwrap().as_mut().unwrap() }
+            }
+
+            /// Invokes the callbacks of all expired alarms and re-programs
+            /// the hardware alarm for the earliest pending one.
+            fn service(&self) {
+                loop {
+                    let mut expired = [None; MAX_ALARMS];
+
+                    let pending = {
+                        let _guard = self.cs.enter();
+
+                        let now = self.now();
+                        let alarms = unsafe { self.alarms.get().as_mut().unwrap() };
+
+                        for (alarm, expired) in alarms.iter_mut().zip(expired.iter_mut()) {
+                            if alarm.timestamp <= now {
+                                alarm.timestamp = u64::MAX;
+                                *expired = alarm.callback;
+                            }
+                        }
+
+                        self.reprogram()
+                    };
+
+                    for (callback, ctx) in expired.iter().flatten() {
+                        callback(*ctx);
+                    }
+
+                    if !pending {
+                        break;
+                    }
+                }
+            }
+
+            /// Programs the hardware alarm for the earliest pending alarm.
+            ///
+            /// Returns `true` if that alarm had already expired by the time the hardware
+            /// alarm was programmed, in which case the hardware alarm might have been missed.
+            fn reprogram(&self) -> bool {
+                let alarms = unsafe { self.alarms.get().as_ref().unwrap() };
+
+                let earliest = alarms.iter().map(|alarm| alarm.timestamp).min();
+
+                match earliest {
+                    Some(earliest) if earliest != u64::MAX => {
+                        let timer = self.timer();
+
+                        timer.set_alarm(earliest).unwrap();
+                        timer.enable_alarm(true).unwrap();
+
+                        self.now() >= earliest
+                    }
+                    _ => false,
+                }
+            }
+        }
+
+        unsafe impl Send for IsrQueueTimerDriver {}
+        unsafe impl Sync for IsrQueueTimerDriver {}
+
+        impl Driver for IsrQueueTimerDriver {
+            fn now(&self) -> u64 {
+                self.timer().counter().unwrap()
+            }
+
+            unsafe fn allocate_alarm(&self) -> Option<AlarmHandle> {
+                // Make sure the timer is initialized outside of an ISR context
+                self.timer();
+
+                let id = self
+                    .allocated
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |allocated| {
+                        if (allocated as usize) < MAX_ALARMS {
+                            Some(allocated + 1)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok()?;
+
+                Some(AlarmHandle::new(id))
+            }
+
+            fn set_alarm_callback(&self, alarm: AlarmHandle, callback: fn(*mut ()), ctx: *mut ()) {
+                let _guard = self.cs.enter();
+
+                let alarms = unsafe { self.alarms.get().as_mut().unwrap() };
+
+                alarms[alarm.id() as usize].callback = Some((callback, ctx));
+            }
+
+            fn set_alarm(&self, alarm: AlarmHandle, timestamp: u64) -> bool {
+                let _guard = self.cs.enter();
+
+                let alarms = unsafe { self.alarms.get().as_mut().unwrap() };
+
+                if timestamp <= self.now() {
+                    alarms[alarm.id() as usize].timestamp = u64::MAX;
+
+                    false
+                } else {
+                    alarms[alarm.id() as usize].timestamp = timestamp;
+
+                    if self.reprogram() && timestamp <= self.now() {
+                        alarms[alarm.id() as usize].timestamp = u64::MAX;
+
+                        false
+                    } else {
+                        true
+                    }
+                }
+            }
+        }
+
+        pub(crate) fn timer_index() -> usize {
+            TimerDriver::<QueueTimer>::timer_index()
+        }
+
+        pub(crate) fn on_alarm() {
+            DRIVER.service();
+        }
+
+        pub fn link() -> i32 {
+            42
+        }
+
+        embassy_time::time_driver_impl!(static DRIVER: IsrQueueTimerDriver = IsrQueueTimerDriver::new());
+    }
+}
@@ -54,6 +54,7 @@ pub type UartConfig = config::Config;
 
 /// UART configuration
 pub mod config {
+    use crate::interrupt::InterruptConfig;
     use crate::units::*;
     use esp_idf_sys::*;
 
@@ -198,6 +199,7 @@ pub mod config {
         pub stop_bits: StopBits,
         pub flow_control: FlowControl,
         pub flow_control_rts_threshold: u8,
+        pub interrupt: InterruptConfig,
     }
 
     impl Config {
@@ -255,6 +257,12 @@ pub mod config {
             self.flow_control_rts_threshold = flow_control_rts_threshold;
             self
         }
+
+        #[must_use]
+        pub fn interrupt(mut self, interrupt: InterruptConfig) -> Self {
+            self.interrupt = interrupt;
+            self
+        }
     }
 
     impl Default for Config {
@@ -266,6 +274,7 @@ pub mod config {
                 stop_bits: StopBits::STOP1,
                 flow_control: FlowControl::None,
                 flow_control_rts_threshold: 122,
+                interrupt: Default::default(),
             }
         }
     }
@@ -343,7 +352,7 @@ impl<'d, UART: Uart> UartDriver<'d, UART> {
                 UART_FIFO_SIZE * 2,
                 0,
                 ptr::null_mut(),
-                0,
+                config.interrupt.into(),
             )
         })?;
 
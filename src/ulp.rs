@@ -1,3 +1,26 @@
+pub mod fsm;
+
+#[cfg(any(
+    all(
+        not(esp_idf_version_major = "4"),
+        esp_idf_ulp_coproc_enabled,
+        not(esp_idf_ulp_coproc_type_fsm)
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s2,
+        esp_idf_esp32s2_ulp_coproc_enabled,
+        esp_idf_esp32s2_ulp_coproc_riscv
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s3,
+        esp_idf_esp32s3_ulp_coproc_enabled,
+        esp_idf_esp32s3_ulp_coproc_riscv
+    )
+))]
+pub use crate::riscv_ulp_hal::mailbox::Mailbox;
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum SleepTimer {
     First = 0,
@@ -400,3 +423,328 @@ impl ULP {
     const TIMER_EN_BIT: u32 =
         esp_idf_sys::RTC_CNTL_ULP_CP_SLP_TIMER_EN_V << esp_idf_sys::RTC_CNTL_ULP_CP_SLP_TIMER_EN_S;
 }
+
+mod sealed {
+    pub trait UlpData {}
+}
+
+/// Plain data types a `UlpVar` can refer to: primitive integers and floats, and arrays of these.
+///
+/// Whatever the ULP program leaves in RTC slow memory is a valid value of these types.
+pub trait UlpData: Copy + sealed::UlpData {}
+
+macro_rules! impl_ulp_data {
+    ($($ty:ty),*) => {
+        $(
+            impl sealed::UlpData for $ty {}
+            impl UlpData for $ty {}
+        )*
+    };
+}
+
+impl_ulp_data!(u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, f32, f64);
+
+impl<T: UlpData, const N: usize> sealed::UlpData for [T; N] {}
+impl<T: UlpData, const N: usize> UlpData for [T; N] {}
+
+/// A typed handle to a global variable of the ULP program, located in RTC slow memory.
+///
+/// Handles are usually created with the `ulp_var!` macro, from the `ulp_`-prefixed symbols
+/// the ESP-IDF build exports for the globals of the ULP program, and are then read and written
+/// with `UlpDriver::read` / `UlpDriver::write`.
+#[cfg(any(
+    all(not(esp_idf_version_major = "4"), esp_idf_ulp_coproc_enabled),
+    all(esp_idf_version_major = "4", esp32, esp_idf_esp32_ulp_coproc_enabled),
+    all(
+        esp_idf_version_major = "4",
+        esp32s2,
+        esp_idf_esp32s2_ulp_coproc_enabled
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s3,
+        esp_idf_esp32s3_ulp_coproc_enabled
+    )
+))]
+#[derive(Debug)]
+pub struct UlpVar<T>(*mut T);
+
+#[cfg(any(
+    all(not(esp_idf_version_major = "4"), esp_idf_ulp_coproc_enabled),
+    all(esp_idf_version_major = "4", esp32, esp_idf_esp32_ulp_coproc_enabled),
+    all(
+        esp_idf_version_major = "4",
+        esp32s2,
+        esp_idf_esp32s2_ulp_coproc_enabled
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s3,
+        esp_idf_esp32s3_ulp_coproc_enabled
+    )
+))]
+impl<T: UlpData> UlpVar<T> {
+    /// Returns `ESP_ERR_INVALID_SIZE` if `ptr` is not within the memory reserved
+    /// for the ULP coprocessor, or is not properly aligned.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a variable of type `T` of the ULP program, i.e. it must not
+    /// overlap with the code of the ULP program or with other variables of a different type.
+    pub unsafe fn new(ptr: *mut T) -> Result<Self, esp_idf_sys::EspError> {
+        Self::check(ptr)
+    }
+}
+
+#[cfg(any(
+    all(not(esp_idf_version_major = "4"), esp_idf_ulp_coproc_enabled),
+    all(esp_idf_version_major = "4", esp32, esp_idf_esp32_ulp_coproc_enabled),
+    all(
+        esp_idf_version_major = "4",
+        esp32s2,
+        esp_idf_esp32s2_ulp_coproc_enabled
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s3,
+        esp_idf_esp32s3_ulp_coproc_enabled
+    )
+))]
+impl<T> UlpVar<T> {
+    /// Address of the variable in the address space of the main CPU
+    pub fn ptr(&self) -> *mut T {
+        self.0
+    }
+
+    /// Byte address of the variable in the address space of the ULP coprocessor
+    pub fn ulp_address(&self) -> u32 {
+        self.0 as u32 - ULP::MEM_START as u32
+    }
+
+    unsafe fn check(ptr: *mut T) -> Result<Self, esp_idf_sys::EspError> {
+        UlpDriver::check_boundaries(ptr)?;
+
+        if (ptr as usize) % core::mem::align_of::<T>() != 0 {
+            esp_idf_sys::esp!(esp_idf_sys::ESP_ERR_INVALID_SIZE)?;
+        }
+
+        Ok(Self(ptr))
+    }
+}
+
+#[cfg(any(
+    all(not(esp_idf_version_major = "4"), esp_idf_ulp_coproc_enabled),
+    all(esp_idf_version_major = "4", esp32, esp_idf_esp32_ulp_coproc_enabled),
+    all(
+        esp_idf_version_major = "4",
+        esp32s2,
+        esp_idf_esp32s2_ulp_coproc_enabled
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s3,
+        esp_idf_esp32s3_ulp_coproc_enabled
+    )
+))]
+impl<T> Clone for UlpVar<T> {
+    fn clone(&self) -> Self {
+        Self(self.0)
+    }
+}
+
+#[cfg(any(
+    all(not(esp_idf_version_major = "4"), esp_idf_ulp_coproc_enabled),
+    all(esp_idf_version_major = "4", esp32, esp_idf_esp32_ulp_coproc_enabled),
+    all(
+        esp_idf_version_major = "4",
+        esp32s2,
+        esp_idf_esp32s2_ulp_coproc_enabled
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s3,
+        esp_idf_esp32s3_ulp_coproc_enabled
+    )
+))]
+unsafe impl<T: Send> Send for UlpVar<T> {}
+
+#[cfg(any(
+    all(not(esp_idf_version_major = "4"), esp_idf_ulp_coproc_enabled),
+    all(esp_idf_version_major = "4", esp32, esp_idf_esp32_ulp_coproc_enabled),
+    all(
+        esp_idf_version_major = "4",
+        esp32s2,
+        esp_idf_esp32s2_ulp_coproc_enabled
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s3,
+        esp_idf_esp32s3_ulp_coproc_enabled
+    )
+))]
+impl<'d> UlpDriver<'d> {
+    pub fn read<T: UlpData>(&self, var: &UlpVar<T>) -> T {
+        unsafe { core::ptr::read_volatile(var.0) }
+    }
+
+    pub fn write<T: UlpData>(&mut self, var: &UlpVar<T>, value: T) {
+        unsafe { core::ptr::write_volatile(var.0, value) }
+    }
+
+    pub fn swap<T: UlpData>(&mut self, var: &UlpVar<T>, value: T) -> T {
+        let old_value = self.read(var);
+
+        self.write(var, value);
+
+        old_value
+    }
+}
+
+#[cfg(any(
+    all(
+        not(esp_idf_version_major = "4"),
+        esp_idf_ulp_coproc_enabled,
+        esp_idf_ulp_coproc_type_fsm
+    ),
+    all(esp_idf_version_major = "4", esp32, esp_idf_esp32_ulp_coproc_enabled),
+    all(
+        esp_idf_version_major = "4",
+        esp32s2,
+        esp_idf_esp32s2_ulp_coproc_enabled,
+        not(esp_idf_esp32s2_ulp_coproc_riscv)
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s3,
+        esp_idf_esp32s3_ulp_coproc_enabled,
+        not(esp_idf_esp32s3_ulp_coproc_riscv)
+    )
+))]
+impl<'d> UlpDriver<'d> {
+    /// Reads a variable stored by the ULP FSM program with `ST`, which writes the value
+    /// in the lower 16 bits of the word and the PC of the `ST` instruction in the upper 16 bits
+    pub fn read_fsm_word(&self, var: &UlpVar<u32>) -> Word {
+        let value = self.read(var);
+
+        Word {
+            pc: ((value >> 16) & 0xffff_u32) as u16,
+            value: (value & 0xffff_u32) as u16,
+        }
+    }
+}
+
+#[cfg(any(
+    all(
+        not(esp_idf_version_major = "4"),
+        esp_idf_ulp_coproc_enabled,
+        not(esp_idf_ulp_coproc_type_fsm)
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s2,
+        esp_idf_esp32s2_ulp_coproc_enabled,
+        esp_idf_esp32s2_ulp_coproc_riscv
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s3,
+        esp_idf_esp32s3_ulp_coproc_enabled,
+        esp_idf_esp32s3_ulp_coproc_riscv
+    )
+))]
+impl<const N: usize> UlpVar<Mailbox<N>> {
+    /// Same as `UlpVar::new`, but for a `Mailbox` of the ULP program
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a `Mailbox<N>` of the ULP program, i.e. it must not
+    /// overlap with the code of the ULP program or with other variables.
+    pub unsafe fn new_mailbox(ptr: *mut Mailbox<N>) -> Result<Self, esp_idf_sys::EspError> {
+        Self::check(ptr)
+    }
+}
+
+#[cfg(any(
+    all(
+        not(esp_idf_version_major = "4"),
+        esp_idf_ulp_coproc_enabled,
+        not(esp_idf_ulp_coproc_type_fsm)
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s2,
+        esp_idf_esp32s2_ulp_coproc_enabled,
+        esp_idf_esp32s2_ulp_coproc_riscv
+    ),
+    all(
+        esp_idf_version_major = "4",
+        esp32s3,
+        esp_idf_esp32s3_ulp_coproc_enabled,
+        esp_idf_esp32s3_ulp_coproc_riscv
+    )
+))]
+impl<'d> UlpDriver<'d> {
+    /// Returns the mailbox the ULP program shares through `var`
+    ///
+    /// The main CPU must stick to one side of the mailbox: either only `pop` the words
+    /// pushed by the ULP program, or only `push` words for it.
+    pub fn mailbox<'a, const N: usize>(&'a self, var: &UlpVar<Mailbox<N>>) -> &'a Mailbox<N> {
+        unsafe { &*var.ptr() }
+    }
+
+    /// Raises the software interrupt of the ULP program, see
+    /// `riscv_ulp_hal::interrupt::subscribe_sw`
+    pub fn trigger_interrupt(&mut self) {
+        #[cfg(esp32s2)]
+        const RTC_CNTL_COCPU_CTRL_REG: u32 = 0x3f408000 + 0x0100;
+        #[cfg(esp32s3)]
+        const RTC_CNTL_COCPU_CTRL_REG: u32 = 0x60008000 + 0x0104;
+        const RTC_CNTL_COCPU_SW_INT_TRIGGER: u32 = 1 << 26;
+
+        unsafe {
+            let reg = RTC_CNTL_COCPU_CTRL_REG as *mut u32;
+            core::ptr::write_volatile(
+                reg,
+                core::ptr::read_volatile(reg) | RTC_CNTL_COCPU_SW_INT_TRIGGER,
+            );
+        }
+    }
+}
+
+/// Declares a function returning a `UlpVar` handle to a global variable of the ULP program.
+///
+/// The ESP-IDF build exports each global `name` of the ULP program as the `ulp_name` symbol,
+/// which is what the macro expects. The type of the variable must either implement `UlpData`,
+/// or be a `Mailbox` (spelled as `Mailbox<N>`).
+///
+/// Example:
+/// ```ignore
+/// esp_idf_hal::ulp_var!(pub counter: u32 = ulp_counter);
+///
+/// let counter = counter()?;
+/// let value = ulp_driver.read(&counter);
+/// ```
+#[macro_export]
+macro_rules! ulp_var {
+    ($vis:vis $name:ident: Mailbox<$n:tt> = $symbol:ident) => {
+        $vis fn $name() -> Result<
+            $crate::ulp::UlpVar<$crate::ulp::Mailbox<$n>>,
+            $crate::sys::EspError,
+        > {
+            extern "C" {
+                static mut $symbol: $crate::ulp::Mailbox<$n>;
+            }
+
+            unsafe { $crate::ulp::UlpVar::new_mailbox(core::ptr::addr_of_mut!($symbol)) }
+        }
+    };
+    ($vis:vis $name:ident: $ty:ty = $symbol:ident) => {
+        $vis fn $name() -> Result<$crate::ulp::UlpVar<$ty>, $crate::sys::EspError> {
+            extern "C" {
+                static mut $symbol: $ty;
+            }
+
+            unsafe { $crate::ulp::UlpVar::new(core::ptr::addr_of_mut!($symbol)) }
+        }
+    };
+}
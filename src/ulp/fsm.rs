@@ -0,0 +1,369 @@
+//! Instruction encoder and assembler for the ULP FSM coprocessor.
+//!
+//! The `const fn`s in this module encode single ULP FSM instructions and can therefore
+//! be used to build a program at compile time, i.e. in a `static` array of words.
+//!
+//! The `Assembler` builds a program at run time, and supports labels as well as
+//! forward references to them, which get resolved (relocated) by `Assembler::assemble`.
+//!
+//! The encodings follow the instruction format described in the ESP32 Technical Reference Manual,
+//! chapter "ULP Coprocessor". All addresses and offsets are expressed in 32-bit words,
+//! the same way the ULP coprocessor expects them.
+//!
+//! Example:
+//! ```ignore
+//! let mut asm = Assembler::<16>::new();
+//!
+//! let counter = asm.label()?;
+//! let start = asm.here()?;
+//!
+//! asm.movi_label(Reg::R1, counter)?;
+//! asm.emit(fsm::ld(Reg::R0, Reg::R1, 0))?;
+//! asm.emit(fsm::addi(Reg::R0, Reg::R0, 1))?;
+//! asm.emit(fsm::st(Reg::R0, Reg::R1, 0))?;
+//! asm.emit(fsm::wake())?;
+//! asm.emit(fsm::halt())?;
+//!
+//! asm.bind(counter)?;
+//! asm.emit(0)?;
+//!
+//! let program = asm.assemble(0)?;
+//!
+//! unsafe { ulp_driver.load(program.as_bytes()) }?;
+//!
+//! // The program was assembled at origin 0, and `ulp_run` expects the entry point in words
+//! let entry = asm.location(start)?.offset() as usize;
+//! unsafe { ulp_driver.start(entry as *const u32) }?;
+//! ```
+
+mod isa;
+
+pub use isa::*;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AsmError {
+    /// The program does not fit in the assembler
+    ProgramFull,
+    /// The assembler cannot hold more labels
+    TooManyLabels,
+    /// The label was already bound to a location
+    LabelRebound(Label),
+    /// The label was referenced, but never bound to a location
+    UnboundLabel(Label),
+    /// The label is too far from the instruction referencing it, or past the addressable memory
+    OutOfRange(Label),
+}
+
+impl core::fmt::Display for AsmError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::ProgramFull => write!(f, "Program full"),
+            Self::TooManyLabels => write!(f, "Too many labels"),
+            Self::LabelRebound(label) => write!(f, "Label {} bound twice", label.0),
+            Self::UnboundLabel(label) => write!(f, "Label {} not bound", label.0),
+            Self::OutOfRange(label) => write!(f, "Label {} out of range", label.0),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AsmError {}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Label(usize);
+
+/// A label bound to a location of an assembled `Program`
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Location(u16);
+
+impl Location {
+    /// Offset (in words) of the location from the start of the program
+    pub fn offset(&self) -> u16 {
+        self.0
+    }
+
+    /// Byte address of the location in the ULP address space, when the program is loaded at `origin` (in words)
+    pub fn address(&self, origin: u16) -> u32 {
+        (origin as u32 + self.0 as u32) * 4
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Relocation {
+    Jump(JumpCondition),
+    JumpR(u16, R0Condition),
+    JumpS(u8, StageCondition),
+    Move(Reg),
+}
+
+/// Assembles a ULP FSM program of at most `N` words, using at most `L` labels
+pub struct Assembler<const N: usize, const L: usize = 32> {
+    code: heapless::Vec<(u32, Option<(Label, Relocation)>), N>,
+    labels: heapless::Vec<Option<u16>, L>,
+}
+
+impl<const N: usize, const L: usize> Assembler<N, L> {
+    pub fn new() -> Self {
+        Self {
+            code: heapless::Vec::new(),
+            labels: heapless::Vec::new(),
+        }
+    }
+
+    /// Current position (in words) from the start of the program
+    pub fn position(&self) -> u16 {
+        self.code.len() as _
+    }
+
+    /// Creates a new label, which is not bound to a location yet
+    pub fn label(&mut self) -> Result<Label, AsmError> {
+        self.labels
+            .push(None)
+            .map_err(|_| AsmError::TooManyLabels)?;
+
+        Ok(Label(self.labels.len() - 1))
+    }
+
+    /// Binds `label` to the current position
+    pub fn bind(&mut self, label: Label) -> Result<(), AsmError> {
+        let position = self.position();
+        let slot = &mut self.labels[label.0];
+
+        if slot.is_some() {
+            Err(AsmError::LabelRebound(label))
+        } else {
+            *slot = Some(position);
+
+            Ok(())
+        }
+    }
+
+    /// Creates a new label bound to the current position
+    pub fn here(&mut self) -> Result<Label, AsmError> {
+        let label = self.label()?;
+
+        self.bind(label)?;
+
+        Ok(label)
+    }
+
+    /// Appends an instruction (or a data word) encoded with one of the functions of this module
+    pub fn emit(&mut self, word: u32) -> Result<(), AsmError> {
+        self.push(word, None)
+    }
+
+    /// Appends an absolute jump to `label`
+    pub fn jump(&mut self, label: Label, condition: JumpCondition) -> Result<(), AsmError> {
+        self.push(0, Some((label, Relocation::Jump(condition))))
+    }
+
+    /// Appends a relative branch to `label`, taken if R0 compares to `threshold` as per `condition`
+    pub fn jumpr(
+        &mut self,
+        label: Label,
+        threshold: u16,
+        condition: R0Condition,
+    ) -> Result<(), AsmError> {
+        self.push(0, Some((label, Relocation::JumpR(threshold, condition))))
+    }
+
+    /// Appends a relative branch to `label`, taken if the stage counter compares to `threshold` as per `condition`
+    pub fn jumps(
+        &mut self,
+        label: Label,
+        threshold: u8,
+        condition: StageCondition,
+    ) -> Result<(), AsmError> {
+        self.push(0, Some((label, Relocation::JumpS(threshold, condition))))
+    }
+
+    /// Appends a `MOVE dst, label`, which loads the word address of `label` into `dst`
+    pub fn movi_label(&mut self, dst: Reg, label: Label) -> Result<(), AsmError> {
+        self.push(0, Some((label, Relocation::Move(dst))))
+    }
+
+    /// Resolves all label references and returns the program, to be loaded at the word address `origin`
+    pub fn assemble(&self, origin: u16) -> Result<Program<N>, AsmError> {
+        let mut words = [0; N];
+
+        for (position, (word, relocation)) in self.code.iter().enumerate() {
+            let word = if let Some((label, relocation)) = relocation {
+                let target = self.labels[label.0].ok_or(AsmError::UnboundLabel(*label))?;
+
+                match relocation {
+                    Relocation::Jump(condition) => {
+                        jump(Self::absolute(*label, origin, target)?, *condition)
+                    }
+                    Relocation::Move(dst) => movi(*dst, Self::absolute(*label, origin, target)?),
+                    Relocation::JumpR(threshold, condition) => jumpr(
+                        Self::relative(*label, position, target)?,
+                        *threshold,
+                        *condition,
+                    ),
+                    Relocation::JumpS(threshold, condition) => jumps(
+                        Self::relative(*label, position, target)?,
+                        *threshold,
+                        *condition,
+                    ),
+                }
+            } else {
+                *word
+            };
+
+            words[position] = word;
+        }
+
+        let text_size = self.code.len() * core::mem::size_of::<u32>();
+
+        Ok(Program {
+            header: [
+                ULP_BINARY_MAGIC,
+                ULP_BINARY_HEADER_SIZE as u32 | (text_size as u32) << 16,
+                0,
+            ],
+            words,
+            len: self.code.len(),
+        })
+    }
+
+    /// Returns the location of `label` in the assembled program
+    pub fn location(&self, label: Label) -> Result<Location, AsmError> {
+        self.labels[label.0]
+            .map(Location)
+            .ok_or(AsmError::UnboundLabel(label))
+    }
+
+    fn push(&mut self, word: u32, relocation: Option<(Label, Relocation)>) -> Result<(), AsmError> {
+        self.code
+            .push((word, relocation))
+            .map_err(|_| AsmError::ProgramFull)
+    }
+
+    fn absolute(label: Label, origin: u16, target: u16) -> Result<u16, AsmError> {
+        let address = origin as u32 + target as u32;
+
+        if address > MAX_JUMP_ADDRESS as u32 {
+            Err(AsmError::OutOfRange(label))
+        } else {
+            Ok(address as _)
+        }
+    }
+
+    fn relative(label: Label, position: usize, target: u16) -> Result<i8, AsmError> {
+        let offset = target as i32 - position as i32;
+
+        if offset.abs() > MAX_BRANCH_OFFSET {
+            Err(AsmError::OutOfRange(label))
+        } else {
+            Ok(offset as _)
+        }
+    }
+}
+
+impl<const N: usize, const L: usize> Default for Assembler<N, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `"ulp\0"`, the magic of `ulp_binary_header_t`
+const ULP_BINARY_MAGIC: u32 = 0x0070_6c75;
+
+/// Size in bytes of `ulp_binary_header_t`, i.e. the offset of the program text in the binary
+const ULP_BINARY_HEADER_SIZE: usize = 12;
+
+/// An assembled ULP FSM program
+///
+/// The words are preceded by a `ulp_binary_header_t` (magic, text offset, and the text, data
+/// and BSS sizes), laid out as three words, which makes the program a binary `ulp_load_binary`
+/// accepts. The whole program is placed in the text section.
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct Program<const N: usize> {
+    header: [u32; 3],
+    words: [u32; N],
+    len: usize,
+}
+
+impl<const N: usize> Program<N> {
+    pub fn words(&self) -> &[u32] {
+        &self.words[..self.len]
+    }
+
+    /// The program including its binary header, as expected by `UlpDriver::load`
+    pub fn as_bytes(&self) -> &[u8] {
+        // `header` and `words` are both `u32` arrays, so `repr(C)` lays them out back to back
+        unsafe {
+            core::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                ULP_BINARY_HEADER_SIZE + self.len * core::mem::size_of::<u32>(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_labels() {
+        let mut asm = Assembler::<8, 4>::new();
+
+        let data = asm.label().unwrap();
+        let exit = asm.label().unwrap();
+
+        let start = asm.here().unwrap();
+        asm.movi_label(Reg::R1, data).unwrap();
+        asm.emit(ld(Reg::R0, Reg::R1, 0)).unwrap();
+        asm.jumpr(exit, 10, R0Condition::Ge).unwrap();
+        asm.jump(start, JumpCondition::Always).unwrap();
+        asm.bind(exit).unwrap();
+        asm.emit(halt()).unwrap();
+        asm.bind(data).unwrap();
+        asm.emit(0).unwrap();
+
+        let program = asm.assemble(0x10).unwrap();
+
+        assert_eq!(
+            program.words(),
+            &[
+                movi(Reg::R1, 0x15),
+                ld(Reg::R0, Reg::R1, 0),
+                jumpr(2, 10, R0Condition::Ge),
+                jump(0x10, JumpCondition::Always),
+                halt(),
+                0,
+            ]
+        );
+
+        let bytes = program.as_bytes();
+
+        assert_eq!(bytes.len(), 12 + 24);
+        assert_eq!(&bytes[..4], b"ulp\0");
+        assert_eq!(&bytes[4..12], &[12, 0, 24, 0, 0, 0, 0, 0]);
+        assert_eq!(&bytes[12..16], &movi(Reg::R1, 0x15).to_ne_bytes());
+        assert_eq!(asm.location(data).unwrap().address(0x10), 0x54);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let mut asm = Assembler::<2, 1>::new();
+
+        let label = asm.label().unwrap();
+        assert_eq!(asm.label(), Err(AsmError::TooManyLabels));
+
+        asm.jump(label, JumpCondition::Always).unwrap();
+        assert_eq!(asm.assemble(0).unwrap_err(), AsmError::UnboundLabel(label));
+
+        asm.bind(label).unwrap();
+        assert_eq!(asm.bind(label), Err(AsmError::LabelRebound(label)));
+        assert_eq!(
+            asm.assemble(MAX_JUMP_ADDRESS).unwrap_err(),
+            AsmError::OutOfRange(label)
+        );
+
+        asm.emit(halt()).unwrap();
+        assert_eq!(asm.emit(halt()), Err(AsmError::ProgramFull));
+    }
+}
@@ -0,0 +1,390 @@
+//! Encoders of single ULP FSM instructions.
+//!
+//! Unlike the rest of the crate, this file depends neither on ESP-IDF nor on other crates,
+//! so its tests can be built and run on the host:
+//! ```sh
+//! rustc --edition 2018 --test src/ulp/fsm/isa.rs -o target/ulp-fsm-isa-tests
+//! target/ulp-fsm-isa-tests
+//! ```
+
+const OPCODE_WR_REG: u32 = 1;
+const OPCODE_RD_REG: u32 = 2;
+const OPCODE_I2C: u32 = 3;
+const OPCODE_DELAY: u32 = 4;
+const OPCODE_ADC: u32 = 5;
+const OPCODE_ST: u32 = 6;
+const OPCODE_ALU: u32 = 7;
+const OPCODE_BRANCH: u32 = 8;
+const OPCODE_END: u32 = 9;
+const OPCODE_TSENS: u32 = 10;
+const OPCODE_HALT: u32 = 11;
+const OPCODE_LD: u32 = 13;
+
+const SUB_OPCODE_ST: u32 = 4;
+
+const SUB_OPCODE_ALU_REG: u32 = 0;
+const SUB_OPCODE_ALU_IMM: u32 = 1;
+const SUB_OPCODE_ALU_STAGE: u32 = 2;
+
+const SUB_OPCODE_BX: u32 = 0;
+const SUB_OPCODE_BR: u32 = 1;
+const SUB_OPCODE_BS: u32 = 2;
+
+const SUB_OPCODE_END: u32 = 0;
+const SUB_OPCODE_SLEEP: u32 = 1;
+
+/// Maximum distance (in words) of the target of a relative branch
+pub const MAX_BRANCH_OFFSET: i32 = 127;
+
+/// Maximum word address of an absolute jump
+pub const MAX_JUMP_ADDRESS: u16 = 0x7ff;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Reg {
+    R0 = 0,
+    R1 = 1,
+    R2 = 2,
+    R3 = 3,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AluOp {
+    Add = 0,
+    Sub = 1,
+    And = 2,
+    Or = 3,
+    Move = 4,
+    Lsh = 5,
+    Rsh = 6,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StageOp {
+    Inc = 0,
+    Dec = 1,
+    Reset = 2,
+}
+
+/// Condition of an absolute jump
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum JumpCondition {
+    Always = 0,
+    /// The result of the last ALU operation was zero
+    Zero = 1,
+    /// The last ALU operation overflowed
+    Overflow = 2,
+}
+
+/// Comparison of R0 with the threshold of a `JUMPR` relative branch
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum R0Condition {
+    Lt = 0,
+    Ge = 1,
+}
+
+/// Comparison of the stage counter with the threshold of a `JUMPS` relative branch
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StageCondition {
+    Lt = 0,
+    Ge = 1,
+    Le = 2,
+}
+
+#[inline(always)]
+const fn field(value: u32, bits: u32, shift: u32) -> u32 {
+    (value & ((1 << bits) - 1)) << shift
+}
+
+#[inline(always)]
+const fn op(opcode: u32) -> u32 {
+    field(opcode, 4, 28)
+}
+
+/// `dst = src1 <op> src2`
+pub const fn alu(alu_op: AluOp, dst: Reg, src1: Reg, src2: Reg) -> u32 {
+    op(OPCODE_ALU)
+        | field(SUB_OPCODE_ALU_REG, 3, 25)
+        | field(alu_op as u32, 4, 21)
+        | field(src2 as u32, 2, 4)
+        | field(src1 as u32, 2, 2)
+        | field(dst as u32, 2, 0)
+}
+
+/// `dst = src <op> imm`
+pub const fn alu_imm(alu_op: AluOp, dst: Reg, src: Reg, imm: u16) -> u32 {
+    op(OPCODE_ALU)
+        | field(SUB_OPCODE_ALU_IMM, 3, 25)
+        | field(alu_op as u32, 4, 21)
+        | field(imm as u32, 16, 4)
+        | field(src as u32, 2, 2)
+        | field(dst as u32, 2, 0)
+}
+
+/// Increments, decrements or resets the stage counter
+pub const fn stage(stage_op: StageOp, imm: u8) -> u32 {
+    op(OPCODE_ALU)
+        | field(SUB_OPCODE_ALU_STAGE, 3, 25)
+        | field(stage_op as u32, 4, 21)
+        | field(imm as u32, 8, 4)
+}
+
+pub const fn add(dst: Reg, src1: Reg, src2: Reg) -> u32 {
+    alu(AluOp::Add, dst, src1, src2)
+}
+
+pub const fn sub(dst: Reg, src1: Reg, src2: Reg) -> u32 {
+    alu(AluOp::Sub, dst, src1, src2)
+}
+
+pub const fn and(dst: Reg, src1: Reg, src2: Reg) -> u32 {
+    alu(AluOp::And, dst, src1, src2)
+}
+
+pub const fn or(dst: Reg, src1: Reg, src2: Reg) -> u32 {
+    alu(AluOp::Or, dst, src1, src2)
+}
+
+pub const fn mov(dst: Reg, src: Reg) -> u32 {
+    alu(AluOp::Move, dst, src, Reg::R0)
+}
+
+pub const fn lsh(dst: Reg, src1: Reg, src2: Reg) -> u32 {
+    alu(AluOp::Lsh, dst, src1, src2)
+}
+
+pub const fn rsh(dst: Reg, src1: Reg, src2: Reg) -> u32 {
+    alu(AluOp::Rsh, dst, src1, src2)
+}
+
+pub const fn addi(dst: Reg, src: Reg, imm: u16) -> u32 {
+    alu_imm(AluOp::Add, dst, src, imm)
+}
+
+pub const fn subi(dst: Reg, src: Reg, imm: u16) -> u32 {
+    alu_imm(AluOp::Sub, dst, src, imm)
+}
+
+pub const fn andi(dst: Reg, src: Reg, imm: u16) -> u32 {
+    alu_imm(AluOp::And, dst, src, imm)
+}
+
+pub const fn ori(dst: Reg, src: Reg, imm: u16) -> u32 {
+    alu_imm(AluOp::Or, dst, src, imm)
+}
+
+pub const fn movi(dst: Reg, imm: u16) -> u32 {
+    alu_imm(AluOp::Move, dst, Reg::R0, imm)
+}
+
+pub const fn lshi(dst: Reg, src: Reg, imm: u16) -> u32 {
+    alu_imm(AluOp::Lsh, dst, src, imm)
+}
+
+pub const fn rshi(dst: Reg, src: Reg, imm: u16) -> u32 {
+    alu_imm(AluOp::Rsh, dst, src, imm)
+}
+
+/// Jumps to the word address `address`
+pub const fn jump(address: u16, condition: JumpCondition) -> u32 {
+    op(OPCODE_BRANCH)
+        | field(SUB_OPCODE_BX, 3, 25)
+        | field(condition as u32, 3, 22)
+        | field(address as u32, 11, 2)
+}
+
+/// Jumps to the word address contained in `reg`
+pub const fn jump_reg(reg: Reg, condition: JumpCondition) -> u32 {
+    op(OPCODE_BRANCH)
+        | field(SUB_OPCODE_BX, 3, 25)
+        | field(condition as u32, 3, 22)
+        | field(1, 1, 21)
+        | field(reg as u32, 2, 0)
+}
+
+const fn branch_offset(offset: i8) -> u32 {
+    let sign = if offset < 0 { 1 } else { 0 };
+
+    field(sign, 1, 7) | field((offset as i32).unsigned_abs(), 7, 0)
+}
+
+/// Branches `offset` words relative to the current instruction, if R0 compares to `threshold` as per `condition`
+pub const fn jumpr(offset: i8, threshold: u16, condition: R0Condition) -> u32 {
+    op(OPCODE_BRANCH)
+        | field(SUB_OPCODE_BR, 3, 25)
+        | field(condition as u32, 1, 24)
+        | field(threshold as u32, 16, 8)
+        | branch_offset(offset)
+}
+
+/// Branches `offset` words relative to the current instruction, if the stage counter compares to `threshold` as per `condition`
+pub const fn jumps(offset: i8, threshold: u8, condition: StageCondition) -> u32 {
+    op(OPCODE_BRANCH)
+        | field(SUB_OPCODE_BS, 3, 25)
+        | field(condition as u32, 2, 23)
+        | field(threshold as u32, 8, 8)
+        | branch_offset(offset)
+}
+
+/// `mem[addr + offset] = src`, with `addr` and `offset` in words
+pub const fn st(src: Reg, addr: Reg, offset: u16) -> u32 {
+    op(OPCODE_ST)
+        | field(SUB_OPCODE_ST, 3, 25)
+        | field(offset as u32, 11, 10)
+        | field(src as u32, 2, 2)
+        | field(addr as u32, 2, 0)
+}
+
+/// `dst = mem[addr + offset]` (lower 16 bits), with `addr` and `offset` in words
+pub const fn ld(dst: Reg, addr: Reg, offset: u16) -> u32 {
+    op(OPCODE_LD)
+        | field(offset as u32, 11, 10)
+        | field(addr as u32, 2, 2)
+        | field(dst as u32, 2, 0)
+}
+
+/// Address of the first RTC peripheral (`DR_REG_RTCCNTL_BASE`) in the main CPU address space
+#[cfg(esp32s2)]
+const RTC_PERIPH_BASE: u32 = 0x3f40_8000;
+#[cfg(esp32s3)]
+const RTC_PERIPH_BASE: u32 = 0x6000_8000;
+/// ESP32, which is also the chip the host tests assume
+#[cfg(not(any(esp32s2, esp32s3)))]
+const RTC_PERIPH_BASE: u32 = 0x3ff4_8000;
+
+/// Peripheral select and word address of an RTC peripheral register,
+/// given its address in the main CPU address space
+const fn reg_address(reg: u32) -> u32 {
+    let offset = reg.wrapping_sub(RTC_PERIPH_BASE);
+
+    field(offset >> 10, 2, 8) | field(offset >> 2, 8, 0)
+}
+
+/// Writes `data` into bits `low..=high` of the RTC register `reg`
+/// (RTC_CNTL, RTC_IO, SENS or RTC_I2C, as addressed by the main CPU).
+///
+/// At most 8 bits can be written at once.
+pub const fn reg_wr(reg: u32, high: u8, low: u8, data: u8) -> u32 {
+    op(OPCODE_WR_REG)
+        | field(high as u32, 5, 23)
+        | field(low as u32, 5, 18)
+        | field(data as u32, 8, 10)
+        | reg_address(reg)
+}
+
+/// Reads bits `low..=high` (at most 16) of the RTC register `reg` into R0
+pub const fn reg_rd(reg: u32, high: u8, low: u8) -> u32 {
+    op(OPCODE_RD_REG) | field(high as u32, 5, 23) | field(low as u32, 5, 18) | reg_address(reg)
+}
+
+/// Samples `channel` of the SAR ADC `sar` (0 or 1) into `dst`
+pub const fn adc(dst: Reg, sar: u8, channel: u8) -> u32 {
+    op(OPCODE_ADC)
+        | field(sar as u32, 1, 6)
+        | field(channel as u32 + 1, 4, 2)
+        | field(dst as u32, 2, 0)
+}
+
+/// Reads bits `low..=high` of register `sub_addr` of the I2C slave selected by `slave_sel` into R0
+pub const fn i2c_rd(sub_addr: u8, high: u8, low: u8, slave_sel: u8) -> u32 {
+    op(OPCODE_I2C)
+        | field(slave_sel as u32, 4, 22)
+        | field(high as u32, 3, 19)
+        | field(low as u32, 3, 16)
+        | field(sub_addr as u32, 8, 0)
+}
+
+/// Writes `data` into bits `low..=high` of register `sub_addr` of the I2C slave selected by `slave_sel`
+pub const fn i2c_wr(sub_addr: u8, data: u8, high: u8, low: u8, slave_sel: u8) -> u32 {
+    op(OPCODE_I2C)
+        | field(1, 1, 27)
+        | field(slave_sel as u32, 4, 22)
+        | field(high as u32, 3, 19)
+        | field(low as u32, 3, 16)
+        | field(data as u32, 8, 8)
+        | field(sub_addr as u32, 8, 0)
+}
+
+/// Waits for `cycles` RTC_FAST_CLK cycles
+pub const fn wait(cycles: u16) -> u32 {
+    op(OPCODE_DELAY) | field(cycles as u32, 16, 0)
+}
+
+/// Measures the temperature sensor into `dst`, waiting `delay` cycles (14 bits)
+pub const fn tsens(dst: Reg, delay: u16) -> u32 {
+    op(OPCODE_TSENS) | field(delay as u32, 14, 2) | field(dst as u32, 2, 0)
+}
+
+/// Wakes up the main CPU
+pub const fn wake() -> u32 {
+    op(OPCODE_END) | field(SUB_OPCODE_END, 3, 25) | field(1, 1, 0)
+}
+
+/// Selects which of the sleep timers (`ulp::SleepTimer`) sets the period of the next ULP wakeup
+pub const fn sleep(timer: u8) -> u32 {
+    op(OPCODE_END) | field(SUB_OPCODE_SLEEP, 3, 25) | field(timer as u32, 4, 0)
+}
+
+/// Halts the ULP coprocessor until its next wakeup
+pub const fn halt() -> u32 {
+    op(OPCODE_HALT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_control_instructions() {
+        assert_eq!(halt(), 0xb000_0000);
+        assert_eq!(wake(), 0x9000_0001);
+        assert_eq!(sleep(3), 0x9200_0003);
+        assert_eq!(wait(0x1234), 0x4000_1234);
+        assert_eq!(tsens(Reg::R2, 0x3fff), 0xa000_fffe);
+    }
+
+    #[test]
+    fn encodes_alu_instructions() {
+        // opcode 7, sub-opcode 0 (reg), sel 0 (add), treg R3, sreg R2, dreg R1
+        assert_eq!(add(Reg::R1, Reg::R2, Reg::R3), 0x7000_0039);
+        assert_eq!(mov(Reg::R3, Reg::R1), 0x7080_0007);
+        // opcode 7, sub-opcode 1 (imm), sel 4 (move)
+        assert_eq!(movi(Reg::R0, 0), 0x7280_0000);
+        assert_eq!(movi(Reg::R2, 0xffff), 0x728f_fff2);
+        assert_eq!(subi(Reg::R1, Reg::R1, 1), 0x7220_0015);
+        assert_eq!(rshi(Reg::R0, Reg::R0, 4), 0x72c0_0040);
+        // opcode 7, sub-opcode 2 (stage), sel 0..=2
+        assert_eq!(stage(StageOp::Inc, 1), 0x7400_0010);
+        assert_eq!(stage(StageOp::Dec, 2), 0x7420_0020);
+        assert_eq!(stage(StageOp::Reset, 0), 0x7440_0000);
+    }
+
+    #[test]
+    fn encodes_memory_instructions() {
+        assert_eq!(st(Reg::R0, Reg::R1, 0), 0x6800_0001);
+        assert_eq!(st(Reg::R2, Reg::R3, 0x7ff), 0x681f_fc0b);
+        assert_eq!(ld(Reg::R0, Reg::R1, 0), 0xd000_0004);
+        assert_eq!(ld(Reg::R3, Reg::R0, 2), 0xd000_0803);
+    }
+
+    #[test]
+    fn encodes_branch_instructions() {
+        assert_eq!(jump(0x10, JumpCondition::Always), 0x8000_0040);
+        assert_eq!(jump(0x7ff, JumpCondition::Zero), 0x8040_1ffc);
+        assert_eq!(jump_reg(Reg::R1, JumpCondition::Overflow), 0x80a0_0001);
+        assert_eq!(jumpr(3, 100, R0Condition::Ge), 0x8300_6403);
+        assert_eq!(jumpr(-3, 100, R0Condition::Lt), 0x8200_6483);
+        assert_eq!(jumps(-1, 10, StageCondition::Le), 0x8500_0a81);
+    }
+
+    #[test]
+    fn encodes_peripheral_instructions() {
+        // RTC_CNTL_STATE0_REG on esp32: periph 0, word address 0x06
+        assert_eq!(reg_wr(0x3ff4_8018, 31, 31, 1), 0x1ffc_0406);
+        // SENS_SAR_MEAS_WAIT2_REG on esp32: periph 2, word address 0x03
+        assert_eq!(reg_rd(0x3ff4_880c, 17, 16), 0x28c0_0203);
+        assert_eq!(adc(Reg::R0, 0, 6), 0x5000_001c);
+        assert_eq!(adc(Reg::R1, 1, 0), 0x5000_0045);
+        assert_eq!(i2c_rd(0x10, 7, 0, 0), 0x3038_0010);
+        assert_eq!(i2c_wr(0x20, 0x5a, 7, 0, 1), 0x3878_5a20);
+    }
+}
@@ -0,0 +1,181 @@
+//! USB OTG full-speed controller
+//!
+//! The ESP32-S2 and ESP32-S3 have a Synopsys DWC2 USB OTG controller, connected to an internal
+//! full-speed PHY on GPIO19 (D-) and GPIO20 (D+). On the ESP32-S3 the internal PHY is shared
+//! with the USB Serial/JTAG controller, and creating a `UsbOtg` switches it over to the OTG
+//! controller - which also ends any USB Serial/JTAG console session.
+//!
+//! With the `usb-otg` feature enabled, `UsbOtg` can be handed to `UsbBus` (re-exported from the
+//! `esp-synopsys-usb-otg` crate), which implements the `usb-device` `UsbBus` trait in device mode.
+//! Device classes - CDC-ACM, HID, MSC etc. - then come from the `usb-device` class crates.
+//!
+//! # Example
+//!
+//! ```
+//! use esp_idf_hal::peripherals::Peripherals;
+//! use esp_idf_hal::usb_otg::{UsbBus, UsbOtg};
+//! use usb_device::prelude::*;
+//!
+//! static mut EP_MEMORY: [u32; 1024] = [0; 1024];
+//!
+//! let peripherals = Peripherals::take().unwrap();
+//! let pins = peripherals.pins;
+//!
+//! let usb = UsbOtg::new(peripherals.usb_otg, pins.gpio19, pins.gpio20);
+//! let usb_bus = UsbBus::new(usb, unsafe { &mut EP_MEMORY });
+//!
+//! let mut serial = usbd_serial::SerialPort::new(&usb_bus);
+//! let mut usb_dev = UsbDeviceBuilder::new(&usb_bus, UsbVidPid(0x303a, 0x3001))
+//!     .device_class(usbd_serial::USB_CLASS_CDC)
+//!     .build();
+//!
+//! loop {
+//!     if usb_dev.poll(&mut [&mut serial]) {
+//!         let mut buf = [0_u8; 64];
+//!
+//!         if let Ok(len) = serial.read(&mut buf) {
+//!             serial.write(&buf[..len]).ok();
+//!         }
+//!     }
+//! }
+//! ```
+
+use esp_idf_sys::*;
+
+use crate::gpio::{self, matrix, Level};
+use crate::peripheral::{Peripheral, PeripheralRef};
+
+#[cfg(feature = "usb-otg")]
+pub use esp_synopsys_usb_otg::UsbBus;
+
+mod regs {
+    use esp_idf_sys::*;
+
+    pub const USB_WRAP_OTG_CONF: usize = DR_REG_USB_WRAP_BASE as usize;
+
+    pub const OTG_CONF_PHY_SEL: u32 = 1 << 2;
+    pub const OTG_CONF_PAD_ENABLE: u32 = 1 << 18;
+    pub const OTG_CONF_AHB_CLK_FORCE_ON: u32 = 1 << 19;
+    pub const OTG_CONF_PHY_CLK_FORCE_ON: u32 = 1 << 20;
+    pub const OTG_CONF_CLK_EN: u32 = 1 << 31;
+
+    #[cfg(esp32s3)]
+    pub const RTC_CNTL_USB_CONF: usize = DR_REG_RTCCNTL_BASE as usize + 0x120;
+
+    #[cfg(esp32s3)]
+    pub const USB_CONF_SW_USB_PHY_SEL: u32 = 1 << 19;
+    #[cfg(esp32s3)]
+    pub const USB_CONF_SW_HW_USB_PHY_SEL: u32 = 1 << 20;
+
+    #[inline(always)]
+    pub fn modify(reg: usize, set: u32, clear: u32) {
+        unsafe {
+            let value = core::ptr::read_volatile(reg as *const u32);
+            core::ptr::write_volatile(reg as *mut u32, (value & !clear) | set);
+        }
+    }
+}
+
+/// The USB OTG controller with its internal PHY set up for device mode
+pub struct UsbOtg<'d> {
+    _usb_otg: PeripheralRef<'d, USB_OTG>,
+    _dm: PeripheralRef<'d, gpio::Gpio19>,
+    _dp: PeripheralRef<'d, gpio::Gpio20>,
+}
+
+impl<'d> UsbOtg<'d> {
+    pub fn new(
+        usb_otg: impl Peripheral<P = USB_OTG> + 'd,
+        dm: impl Peripheral<P = gpio::Gpio19> + 'd,
+        dp: impl Peripheral<P = gpio::Gpio20> + 'd,
+    ) -> Self {
+        crate::into_ref!(usb_otg, dm, dp);
+
+        unsafe {
+            periph_module_enable(periph_module_t_PERIPH_USB_MODULE);
+        }
+
+        // Route the internal PHY to the OTG controller
+        #[cfg(esp32s3)]
+        regs::modify(
+            regs::RTC_CNTL_USB_CONF,
+            regs::USB_CONF_SW_HW_USB_PHY_SEL | regs::USB_CONF_SW_USB_PHY_SEL,
+            0,
+        );
+
+        regs::modify(
+            regs::USB_WRAP_OTG_CONF,
+            regs::OTG_CONF_PAD_ENABLE
+                | regs::OTG_CONF_CLK_EN
+                | regs::OTG_CONF_AHB_CLK_FORCE_ON
+                | regs::OTG_CONF_PHY_CLK_FORCE_ON,
+            regs::OTG_CONF_PHY_SEL,
+        );
+
+        // There is no ID pin nor VBUS sensing: pretend to be a B-device (device side of the cable),
+        // attached to a valid VBUS
+        matrix::connect_constant(matrix::InputSignal::UsbOtgIdDig, Level::High);
+        matrix::connect_constant(matrix::InputSignal::UsbSrpBValid, Level::High);
+        matrix::connect_constant(matrix::InputSignal::UsbOtgVBusValid, Level::High);
+        matrix::connect_constant(matrix::InputSignal::UsbOtgAValid, Level::Low);
+
+        unsafe {
+            gpio_set_drive_capability(19, gpio_drive_cap_t_GPIO_DRIVE_CAP_3);
+            gpio_set_drive_capability(20, gpio_drive_cap_t_GPIO_DRIVE_CAP_3);
+        }
+
+        Self {
+            _usb_otg: usb_otg,
+            _dm: dm,
+            _dp: dp,
+        }
+    }
+}
+
+impl<'d> Drop for UsbOtg<'d> {
+    fn drop(&mut self) {
+        regs::modify(
+            regs::USB_WRAP_OTG_CONF,
+            0,
+            regs::OTG_CONF_PAD_ENABLE
+                | regs::OTG_CONF_CLK_EN
+                | regs::OTG_CONF_AHB_CLK_FORCE_ON
+                | regs::OTG_CONF_PHY_CLK_FORCE_ON,
+        );
+
+        // Hand the internal PHY back to the USB Serial/JTAG controller
+        #[cfg(esp32s3)]
+        regs::modify(
+            regs::RTC_CNTL_USB_CONF,
+            0,
+            regs::USB_CONF_SW_HW_USB_PHY_SEL | regs::USB_CONF_SW_USB_PHY_SEL,
+        );
+
+        unsafe {
+            periph_module_disable(periph_module_t_PERIPH_USB_MODULE);
+        }
+    }
+}
+
+// `UsbBus` requires its peripheral to be `Sync`, and `UsbOtg` does not access the hardware through `&self`
+unsafe impl<'d> Sync for UsbOtg<'d> {}
+
+#[cfg(feature = "usb-otg")]
+unsafe impl<'d> esp_synopsys_usb_otg::UsbPeripheral for UsbOtg<'d> {
+    const REGISTERS: *const () = DR_REG_USB_BASE as usize as *const ();
+
+    const HIGH_SPEED: bool = false;
+    const FIFO_DEPTH_WORDS: usize = 256;
+    // 7 endpoints in total, but only 5 IN endpoints - EP0 included - can be active at once
+    const ENDPOINT_COUNT: usize = 5;
+
+    fn enable() {
+        // Clocks and PHY are already set up by `UsbOtg::new`
+    }
+
+    fn ahb_frequency_hz(&self) -> u32 {
+        80_000_000
+    }
+}
+
+crate::impl_peripheral!(USB_OTG);
@@ -0,0 +1,294 @@
+//! USB Serial/JTAG controller
+//!
+//! The ESP32-S3 and ESP32-C3 have a built-in USB device which - next to a JTAG interface -
+//! exposes a CDC-ACM serial port to the host, without any external USB-to-UART bridge.
+//! The D- and D+ pins are fixed (GPIO19 and GPIO20 on the ESP32-S3, GPIO18 and GPIO19
+//! on the ESP32-C3).
+//!
+//! The serial port is often also used as the ESP-IDF console; the driver can still be
+//! installed, with the console output interleaving with the output of the driver.
+//!
+//! # Example
+//!
+//! ```
+//! use core::fmt::Write;
+//! use esp_idf_hal::peripherals::Peripherals;
+//! use esp_idf_hal::usb_serial::{config, UsbSerialDriver};
+//!
+//! let peripherals = Peripherals::take().unwrap();
+//!
+//! let mut serial = UsbSerialDriver::new(peripherals.usb_serial, &config::Config::new())?;
+//!
+//! writeln!(serial, "Hello from the USB Serial/JTAG controller").unwrap();
+//! ```
+
+use esp_idf_sys::*;
+
+use crate::delay::{BLOCK, NON_BLOCK};
+use crate::peripheral::{Peripheral, PeripheralRef};
+use crate::uart::SerialError;
+
+pub type UsbSerialConfig = config::Config;
+
+/// Before `usb_serial_jtag_wait_tx_done()`, whether the driver is done has to be read
+/// from the controller itself
+#[cfg(any(
+    esp_idf_version_major = "4",
+    esp_idf_version = "5.0",
+    esp_idf_version = "5.1",
+    esp_idf_version = "5.2"
+))]
+mod regs {
+    #[cfg(esp32c3)]
+    const USB_SERIAL_JTAG_BASE: usize = 0x6004_3000;
+    #[cfg(esp32s3)]
+    const USB_SERIAL_JTAG_BASE: usize = 0x6003_8000;
+
+    pub const USB_SERIAL_JTAG_EP1_CONF: usize = USB_SERIAL_JTAG_BASE + 0x04;
+    pub const USB_SERIAL_JTAG_INT_ENA: usize = USB_SERIAL_JTAG_BASE + 0x10;
+
+    pub const EP1_CONF_SERIAL_IN_EP_DATA_FREE: u32 = 1 << 1;
+    pub const INT_SERIAL_IN_EMPTY: u32 = 1 << 3;
+
+    #[inline(always)]
+    pub fn read(reg: usize) -> u32 {
+        unsafe { core::ptr::read_volatile(reg as *const u32) }
+    }
+
+    /// The driver ISR keeps the IN-empty interrupt enabled for as long as its ring buffer
+    /// holds data, and the IN endpoint is free once the host has picked up the last packet
+    pub fn tx_done() -> bool {
+        read(USB_SERIAL_JTAG_INT_ENA) & INT_SERIAL_IN_EMPTY == 0
+            && read(USB_SERIAL_JTAG_EP1_CONF) & EP1_CONF_SERIAL_IN_EP_DATA_FREE != 0
+    }
+}
+
+pub mod config {
+    /// USB Serial/JTAG driver configuration
+    #[derive(Copy, Clone, Debug)]
+    pub struct Config {
+        pub tx_buffer_size: usize,
+        pub rx_buffer_size: usize,
+    }
+
+    impl Config {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        /// Size of the ring buffer written data is queued in until the host picks it up
+        #[must_use]
+        pub fn tx_buffer_size(mut self, tx_buffer_size: usize) -> Self {
+            self.tx_buffer_size = tx_buffer_size;
+            self
+        }
+
+        /// Size of the ring buffer received data is queued in until read
+        #[must_use]
+        pub fn rx_buffer_size(mut self, rx_buffer_size: usize) -> Self {
+            self.rx_buffer_size = rx_buffer_size;
+            self
+        }
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                tx_buffer_size: 256,
+                rx_buffer_size: 256,
+            }
+        }
+    }
+}
+
+/// USB Serial/JTAG abstraction
+pub struct UsbSerialDriver<'d> {
+    _usb_serial: PeripheralRef<'d, USB_SERIAL>,
+}
+
+impl<'d> UsbSerialDriver<'d> {
+    pub fn new(
+        usb_serial: impl Peripheral<P = USB_SERIAL> + 'd,
+        config: &config::Config,
+    ) -> Result<Self, EspError> {
+        crate::into_ref!(usb_serial);
+
+        let mut driver_config = usb_serial_jtag_driver_config_t {
+            tx_buffer_size: config.tx_buffer_size as _,
+            rx_buffer_size: config.rx_buffer_size as _,
+        };
+
+        esp!(unsafe { usb_serial_jtag_driver_install(&mut driver_config) })?;
+
+        Ok(Self {
+            _usb_serial: usb_serial,
+        })
+    }
+
+    /// Whether a host is connected and polling the device
+    ///
+    /// Detected from the USB start-of-frame packets, which a host sends every millisecond.
+    #[cfg(not(any(esp_idf_version_major = "4", esp_idf_version = "5.0")))]
+    pub fn is_connected(&self) -> bool {
+        unsafe { usb_serial_jtag_is_connected() }
+    }
+
+    /// Read multiple bytes into a slice; block until specified timeout
+    pub fn read(&mut self, buf: &mut [u8], delay: TickType_t) -> Result<usize, EspError> {
+        // `usb_serial_jtag_read_bytes()` returns how many bytes were read out,
+        // 0 meaning a timeout with nothing read out
+        let len = unsafe {
+            usb_serial_jtag_read_bytes(buf.as_mut_ptr() as *mut _, buf.len() as _, delay)
+        };
+
+        if len >= 0 {
+            Ok(len as usize)
+        } else {
+            Err(EspError::from(ESP_ERR_INVALID_STATE).unwrap())
+        }
+    }
+
+    /// Write multiple bytes from a slice; block until specified timeout
+    ///
+    /// The data is queued until the host picks it up, so with no host connected,
+    /// writing blocks - or returns 0 - once the TX ring buffer is full.
+    pub fn write(&mut self, bytes: &[u8], delay: TickType_t) -> Result<usize, EspError> {
+        let len = unsafe {
+            usb_serial_jtag_write_bytes(bytes.as_ptr() as *const _, bytes.len() as _, delay)
+        };
+
+        if len >= 0 {
+            Ok(len as usize)
+        } else {
+            Err(EspError::from(ESP_ERR_INVALID_STATE).unwrap())
+        }
+    }
+
+    /// Wait until all queued data has been picked up by the host; block until specified timeout
+    ///
+    /// Returns `ESP_ERR_TIMEOUT` if data is still pending once the timeout expires,
+    /// e.g. because no host is connected.
+    pub fn wait_tx_done(&mut self, delay: TickType_t) -> Result<(), EspError> {
+        #[cfg(not(any(
+            esp_idf_version_major = "4",
+            esp_idf_version = "5.0",
+            esp_idf_version = "5.1",
+            esp_idf_version = "5.2"
+        )))]
+        esp!(unsafe { usb_serial_jtag_wait_tx_done(delay) })?;
+
+        #[cfg(any(
+            esp_idf_version_major = "4",
+            esp_idf_version = "5.0",
+            esp_idf_version = "5.1",
+            esp_idf_version = "5.2"
+        ))]
+        {
+            let start = unsafe { xTaskGetTickCount() };
+
+            while !regs::tx_done() {
+                if delay != BLOCK && unsafe { xTaskGetTickCount() }.wrapping_sub(start) >= delay {
+                    return Err(EspError::from(ESP_ERR_TIMEOUT).unwrap());
+                }
+
+                unsafe { vTaskDelay(1) };
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check - without blocking - whether all queued data has been picked up by the host;
+    /// returns `ESP_ERR_TIMEOUT` if data is still pending
+    pub fn flush(&mut self) -> Result<(), EspError> {
+        self.wait_tx_done(NON_BLOCK)
+    }
+}
+
+impl<'d> Drop for UsbSerialDriver<'d> {
+    fn drop(&mut self) {
+        esp!(unsafe { usb_serial_jtag_driver_uninstall() }).unwrap();
+    }
+}
+
+impl<'d> embedded_hal::serial::ErrorType for UsbSerialDriver<'d> {
+    type Error = SerialError;
+}
+
+impl<'d> embedded_hal_0_2::serial::Read<u8> for UsbSerialDriver<'d> {
+    type Error = SerialError;
+
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let mut buf = [0_u8];
+
+        let result = self.read(&mut buf, NON_BLOCK);
+
+        check_nb(result, buf[0])
+    }
+}
+
+impl<'d> embedded_hal_nb::serial::Read<u8> for UsbSerialDriver<'d> {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let mut buf = [0_u8];
+
+        let result = self.read(&mut buf, NON_BLOCK);
+
+        check_nb(result, buf[0])
+    }
+}
+
+impl<'d> embedded_hal_0_2::serial::Write<u8> for UsbSerialDriver<'d> {
+    type Error = SerialError;
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        UsbSerialDriver::flush(self).map_err(to_nb_err)
+    }
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+        check_nb(self.write(&[byte], NON_BLOCK), ())
+    }
+}
+
+impl<'d> embedded_hal_nb::serial::Write<u8> for UsbSerialDriver<'d> {
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        UsbSerialDriver::flush(self).map_err(to_nb_err)
+    }
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+        check_nb(self.write(&[byte], NON_BLOCK), ())
+    }
+}
+
+impl<'d> core::fmt::Write for UsbSerialDriver<'d> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let buf = s.as_bytes();
+        let mut offset = 0;
+
+        while offset < buf.len() {
+            offset += self
+                .write(&buf[offset..], BLOCK)
+                .map_err(|_| core::fmt::Error)?
+        }
+
+        Ok(())
+    }
+}
+
+fn to_nb_err(err: EspError) -> nb::Error<SerialError> {
+    if err.code() == ESP_ERR_TIMEOUT {
+        nb::Error::WouldBlock
+    } else {
+        nb::Error::Other(SerialError::other(err))
+    }
+}
+
+fn check_nb<T>(result: Result<usize, EspError>, value: T) -> nb::Result<T, SerialError> {
+    match result {
+        Ok(1) => Ok(value),
+        Ok(0) => Err(nb::Error::WouldBlock),
+        Ok(_) => unreachable!(),
+        Err(err) => Err(nb::Error::Other(SerialError::other(err))),
+    }
+}
+
+crate::impl_peripheral!(USB_SERIAL);